@@ -1,12 +1,16 @@
+use std::convert::TryFrom;
+use std::fmt;
 use std::net::Ipv4Addr;
+use std::str::FromStr;
 
 use chrono::{prelude::*, DateTime};
 use enum_iterator::IntoEnumIterator;
+use regex::Regex;
 
 /// Meta stores the parsed result of an article meta.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Meta {
-    pub board: BoardName,
+    pub board: Board,
     pub id: String,
     pub category: String,
     pub title: String,
@@ -16,31 +20,230 @@ pub struct Meta {
     pub ip: Option<Ipv4Addr>,
 }
 
+impl Meta {
+    /// Return the Unix timestamp of article creation from its date, or its ID
+    /// if the date is absent.
+    pub fn timestamp(&self) -> Option<i64> {
+        self.date
+            .map(|d| d.timestamp())
+            .or_else(|| timestamp_from_id(&self.id))
+    }
+}
+
 /// Article stores the parsed result of an article.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Article {
     pub meta: Meta,
     pub content: String,
+    /// Content separated into author's text, quotations and signature
+    #[serde(default)]
+    pub body: ContentBody,
+    /// Links in content, in the order they appear
+    #[serde(default)]
+    pub links: Vec<Link>,
     pub reply_count: ReplyCount,
     pub replies: Vec<Reply>,
+    #[serde(default)]
+    pub edits: Vec<Edit>,
+    #[serde(default)]
+    pub crossposts: Vec<Crosspost>,
+    /// Original article if the article was forwarded from another board
+    #[serde(default)]
+    pub crossposted_from: Option<CrosspostSource>,
+    #[serde(default)]
+    pub moderation_events: Vec<ModerationEvent>,
+}
+
+/// ContentBody represents the content of article separated into parts.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ContentBody {
+    /// Text written by author, without quotations and signature
+    pub text: String,
+    pub quotes: Vec<Quote>,
+    /// Signature after the line "--"
+    pub signature: Option<String>,
+}
+
+/// Quote represents consecutive lines quoted from another article.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Quote {
+    /// Author of quoted article, from the header like "※ 引述《someone (某人)》之銘言："
+    pub author_id: Option<String>,
+    /// Quoted lines without the leading ": "
+    pub lines: Vec<String>,
 }
 
 /// ReplyCount represents the number info about an article.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct ReplyCount {
     pub push: i16,
     pub neutral: i16,
     pub boo: i16,
 }
 
+impl ReplyCount {
+    pub fn from_replies(replies: &[Reply]) -> ReplyCount {
+        let count_of = |reply_type: ReplyType| {
+            replies
+                .iter()
+                .filter(|r| r.reply_type == reply_type)
+                .count() as i16
+        };
+        ReplyCount {
+            push: count_of(ReplyType::Push),
+            neutral: count_of(ReplyType::Neutral),
+            boo: count_of(ReplyType::Boo),
+        }
+    }
+}
+
+impl Article {
+    /// Return the ReplyCount of replies after push count was reset by moderator
+    /// last time, which is the push count shown in board listing.
+    pub fn reply_count_since_reset(&self) -> ReplyCount {
+        let start = self
+            .moderation_events
+            .iter()
+            .rev()
+            .find(|e| e.action == ModerationAction::ResetPushCount)
+            .map_or(0, |e| e.position);
+        ReplyCount::from_replies(&self.replies[start.min(self.replies.len())..])
+    }
+}
+
 /// Reply represents a reply.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Reply {
     pub reply_type: ReplyType,
     pub author_id: String,
     pub ip: Option<Ipv4Addr>,
     pub date: Option<DateTime<FixedOffset>>,
     pub content: String,
+    #[serde(default)]
+    pub links: Vec<Link>,
+}
+
+impl Reply {
+    /// Whether both are the same reply, regardless of fields derived from
+    /// content like links, which are absent in replies stored by older versions.
+    pub fn is_same(&self, other: &Reply) -> bool {
+        self.reply_type == other.reply_type
+            && self.author_id == other.author_id
+            && self.content == other.content
+            && self.date == other.date
+    }
+}
+
+/// Link represents a URL or a reference to PTT article like "#1VDrjZHu (Gossiping)".
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Link {
+    pub url: String,
+    pub kind: LinkKind,
+}
+
+/// LinkKind represents what a link refers to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum LinkKind {
+    /// Image or image host like imgur
+    Image,
+    YouTube,
+    PttArticle {
+        board: Board,
+        id: String,
+        aid: Option<String>,
+    },
+    Other,
+}
+
+/// Edit represents a record of the author or moderator editing an article.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Edit {
+    pub editor_id: String,
+    pub ip: Option<Ipv4Addr>,
+    pub date: Option<DateTime<FixedOffset>>,
+}
+
+/// ModerationEvent represents an action of moderator shown among replies,
+/// e.g. "※hateOnas 於 01/06/2014 18:38:19 將推薦值歸零".
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ModerationEvent {
+    /// Number of replies before the event
+    pub position: usize,
+    pub moderator_id: String,
+    pub date: Option<DateTime<FixedOffset>>,
+    pub action: ModerationAction,
+}
+
+/// ModerationAction represents the type of action of moderator.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ModerationAction {
+    /// Push count of article was reset to zero
+    ResetPushCount,
+    /// Other action with its description
+    Other(String),
+}
+
+/// Crosspost represents a record of forwarding an article to another board.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Crosspost {
+    pub user_id: String,
+    pub board: Board,
+    pub date: Option<DateTime<FixedOffset>>,
+}
+
+/// CrosspostSource represents the original article which a forwarded article
+/// was copied from.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CrosspostSource {
+    pub board: Board,
+    /// AID of the original article, e.g. "1VDrjZHu"
+    pub aid: Option<String>,
+    /// ID of the original article converted from AID, e.g. "M.1597463395.A.478"
+    pub id: Option<String>,
+}
+
+/// ReplyUpdate represents the replies of an article changed since it was
+/// crawled previously. Replies of the previous article from `offset` on are
+/// replaced by `replies`, which usually means `replies` are appended.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReplyUpdate {
+    pub board: Board,
+    pub id: String,
+    pub offset: usize,
+    pub replies: Vec<Reply>,
+    pub reply_count: ReplyCount,
+}
+
+impl ReplyUpdate {
+    /// Compare the replies of previous and refreshed versions of an article.
+    pub fn diff(previous: &Article, refreshed: &Article) -> ReplyUpdate {
+        let offset = previous
+            .replies
+            .iter()
+            .zip(refreshed.replies.iter())
+            .take_while(|(p, r)| p.is_same(r))
+            .count();
+        ReplyUpdate {
+            board: refreshed.meta.board.clone(),
+            id: refreshed.meta.id.clone(),
+            offset,
+            replies: refreshed.replies[offset..].to_vec(),
+            reply_count: refreshed.reply_count.clone(),
+        }
+    }
+
+    /// Whether the replies are the same as previous ones.
+    pub fn is_empty(&self, previous: &Article) -> bool {
+        self.replies.is_empty() && self.offset == previous.replies.len()
+    }
+
+    /// Update the replies and reply count of previous article to the refreshed ones.
+    pub fn apply(&self, previous: &mut Article) {
+        previous.replies.truncate(self.offset);
+        previous.replies.extend(self.replies.iter().cloned());
+        previous.reply_count = self.reply_count.clone();
+    }
 }
 
 /// ReplyType represents the type of a reply.
@@ -54,7 +257,284 @@ pub enum ReplyType {
     Boo,
 }
 
-/// BoardName represents the name of a board.
+/// BoardListingEntry represents an article listed in an index page of board.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BoardListingEntry {
+    pub push_count: PushCount,
+    pub mark: Option<String>,
+    /// Date shown in listing, e.g. "8/15"
+    pub date: String,
+    pub author_id: String,
+    pub title: String,
+    /// URL of article, or None if the article was deleted
+    pub url: Option<String>,
+    /// Whether the article is pinned at the bottom of the latest page
+    pub pinned: bool,
+    /// Number of the index page, or None if unknown
+    pub page: Option<u32>,
+    /// Position of the entry in the index page, starting from 0
+    pub position: usize,
+    /// Deletion info if the article was deleted
+    pub deletion: Option<Deletion>,
+}
+
+/// Deletion represents who deleted an article listed in board listing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Deletion {
+    /// ID of the user who deleted the article, which is the author if not shown
+    pub deleted_by: Option<String>,
+    /// ID of the original author if shown
+    pub author_id: Option<String>,
+}
+
+/// PushCount represents the push count (推文數) of an article shown in board listing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PushCount {
+    /// Net number of pushes between 0 and 99
+    Count(u8),
+    /// "爆", 100 or more net pushes
+    Explosive,
+    /// "X1" to "X9", at least 10 times the number of net boos
+    NegativeTens(u8),
+    /// "XX", 100 or more net boos
+    NegativeExplosive,
+}
+
+impl FromStr for PushCount {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "" => Ok(PushCount::Count(0)),
+            "爆" => Ok(PushCount::Explosive),
+            "XX" => Ok(PushCount::NegativeExplosive),
+            _ => {
+                if let Some(tens) = s.strip_prefix('X') {
+                    tens.parse::<u8>()
+                        .map(PushCount::NegativeTens)
+                        .map_err(|_| ())
+                } else {
+                    s.parse::<u8>().map(PushCount::Count).map_err(|_| ())
+                }
+            }
+        }
+    }
+}
+
+/// BoardInfo represents a board listed in hot boards or classification pages.
+/// Moderators of board are not included, since the web version of PTT shows
+/// them in neither these pages nor the index pages of board.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BoardInfo {
+    pub name: Board,
+    /// Chinese title of the board
+    pub title: String,
+    pub category: String,
+    /// Number of users reading the board, or None if not shown as number
+    pub user_count: Option<u32>,
+    pub url: String,
+}
+
+/// CategoryInfo represents a category listed in classification pages.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CategoryInfo {
+    pub name: String,
+    pub title: String,
+    pub url: String,
+}
+
+/// BoardEntry represents an entry of hot boards or classification pages.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum BoardEntry {
+    Board(BoardInfo),
+    Category(CategoryInfo),
+}
+
+/// BoardCategory represents a category of the classification tree with its
+/// boards and sub-categories.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BoardCategory {
+    pub info: CategoryInfo,
+    pub boards: Vec<BoardInfo>,
+    pub categories: Vec<BoardCategory>,
+}
+
+/// ManListingEntry represents a directory or an article listed in a page of
+/// the digest (精華區) of board.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ManListingEntry {
+    pub title: String,
+    pub url: String,
+    pub is_directory: bool,
+}
+
+/// ManNode represents a directory or an article crawled from the digest
+/// (精華區) of board. `path` is the titles of directories from the root of
+/// digest down to the node itself.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type")]
+pub enum ManNode {
+    Directory {
+        path: Vec<String>,
+        url: String,
+    },
+    Article {
+        path: Vec<String>,
+        url: String,
+        article: Box<Article>,
+    },
+}
+
+/// FeedEntry represents an entry in the Atom feed of board.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FeedEntry {
+    /// ID of the article, e.g. "M.1597463395.A.478"
+    pub id: String,
+    pub title: String,
+    pub author_id: String,
+    pub published: Option<DateTime<FixedOffset>>,
+    pub updated: Option<DateTime<FixedOffset>>,
+    pub url: String,
+}
+
+/// Return the ID of article from its URL.
+/// e.g. "M.1597463395.A.478" of "https://www.ptt.cc/bbs/Gossiping/M.1597463395.A.478.html"
+pub fn id_from_url(url: &str) -> Option<&str> {
+    url.rsplit('/').next()?.strip_suffix(".html")
+}
+
+/// Return the Unix timestamp of article creation embedded in its ID.
+/// e.g. 1597463395 of "M.1597463395.A.478"
+pub fn timestamp_from_id(id: &str) -> Option<i64> {
+    id.split('.').nth(1)?.parse::<i64>().ok()
+}
+
+const AID_CHARS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz-_";
+
+/// Return the ID of article from its AID.
+/// e.g. "M.1597463395.A.478" of "1VDrjZHu"
+pub fn id_from_aid(aid: &str) -> Option<String> {
+    let aid = aid.trim_start_matches('#');
+    if aid.is_empty() || aid.len() > 8 {
+        return None;
+    }
+    let mut value: u64 = 0;
+    for c in aid.bytes() {
+        let digit = AID_CHARS.iter().position(|&a| a == c)? as u64;
+        value = (value << 6) | digit;
+    }
+    let prefix = match value >> 44 {
+        0 => "M",
+        1 => "G",
+        _ => return None,
+    };
+    let timestamp = (value >> 12) & 0xFFFF_FFFF;
+    let random = value & 0xFFF;
+    Some(format!("{}.{}.A.{:03X}", prefix, timestamp, random))
+}
+
+/// Return the AID of article from its ID.
+/// e.g. "1VDrjZHu" of "M.1597463395.A.478"
+pub fn aid_from_id(id: &str) -> Option<String> {
+    let parts: Vec<&str> = id.split('.').collect();
+    if parts.len() != 4 {
+        return None;
+    }
+    let prefix: u64 = match parts[0] {
+        "M" => 0,
+        "G" => 1,
+        _ => return None,
+    };
+    let timestamp = parts[1].parse::<u32>().ok()? as u64;
+    let random = u64::from_str_radix(parts[3], 16).ok()?;
+    if random > 0xFFF {
+        return None;
+    }
+    let mut value = (prefix << 44) | (timestamp << 12) | random;
+    let mut aid = vec![0; 8];
+    for c in aid.iter_mut().rev() {
+        *c = AID_CHARS[(value & 63) as usize];
+        value >>= 6;
+    }
+    String::from_utf8(aid).ok()
+}
+
+/// Board represents the name of any board on PTT, e.g. "Gossiping" or "Soft_Job".
+/// Names of known boards are normalized to the same case as BoardName.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct Board(String);
+
+impl Board {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Return the BoardName if the board is one of the known boards.
+    pub fn known(&self) -> Option<BoardName> {
+        self.0
+            .parse::<BoardName>()
+            .ok()
+            .filter(|b| *b != BoardName::Unknown)
+    }
+}
+
+impl FromStr for Board {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"^[A-Za-z0-9][A-Za-z0-9_\-]{0,11}$").unwrap();
+        }
+
+        if !RE.is_match(s) {
+            return Err(format!("Invalid board name \"{}\"", s));
+        }
+        let name = match BoardName::into_enum_iter()
+            .filter(|b| *b != BoardName::Unknown)
+            .map(|b| b.to_string())
+            .find(|b| b.eq_ignore_ascii_case(s))
+        {
+            Some(known_name) => known_name,
+            None => s.to_owned(),
+        };
+        Ok(Board(name))
+    }
+}
+
+impl TryFrom<String> for Board {
+    type Error = String;
+
+    /// Besides board names, accept names of BoardName variants like "SoftJob",
+    /// which is how boards were serialized before Board was introduced.
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match BoardName::into_enum_iter().find(|b| format!("{:?}", b) == s) {
+            Some(board) => Ok(board.into()),
+            None => s.parse::<Board>(),
+        }
+    }
+}
+
+impl From<Board> for String {
+    fn from(board: Board) -> String {
+        board.0
+    }
+}
+
+impl From<BoardName> for Board {
+    fn from(board: BoardName) -> Board {
+        Board(board.to_string())
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// BoardName represents the name of a known board.
 /// Most of them are extracted from https://www.ptt.cc/bbs/hotboards.html
 #[derive(Serialize, Deserialize, Clone, Debug, EnumString, Display, PartialEq, IntoEnumIterator)]
 pub enum BoardName {
@@ -226,3 +706,210 @@ pub enum BoardName {
     EASeries,
     Unknown,
 }
+
+/// Return an article of Gossiping with given ID and empty fields, which is
+/// shared by tests of modules.
+#[cfg(test)]
+pub(crate) fn test_article(id: &str) -> Article {
+    Article {
+        meta: Meta {
+            board: BoardName::Gossiping.into(),
+            id: id.to_owned(),
+            category: "".to_owned(),
+            title: "".to_owned(),
+            author_id: "someone".to_owned(),
+            author_name: None,
+            date: None,
+            ip: None,
+        },
+        content: "".to_owned(),
+        body: ContentBody::default(),
+        links: vec![],
+        reply_count: ReplyCount {
+            push: 0,
+            neutral: 0,
+            boo: 0,
+        },
+        replies: vec![],
+        edits: vec![],
+        crossposts: vec![],
+        crossposted_from: None,
+        moderation_events: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_push_count() {
+        assert_eq!("".parse::<PushCount>(), Ok(PushCount::Count(0)));
+        assert_eq!("42".parse::<PushCount>(), Ok(PushCount::Count(42)));
+        assert_eq!("爆".parse::<PushCount>(), Ok(PushCount::Explosive));
+        assert_eq!("X3".parse::<PushCount>(), Ok(PushCount::NegativeTens(3)));
+        assert_eq!("XX".parse::<PushCount>(), Ok(PushCount::NegativeExplosive));
+        assert!("?".parse::<PushCount>().is_err());
+    }
+
+    #[test]
+    fn test_parse_board() {
+        let board = "Python".parse::<Board>().unwrap();
+        assert_eq!(board.as_str(), "Python");
+        assert_eq!(board.known(), None);
+
+        let board = "soft_job".parse::<Board>().unwrap();
+        assert_eq!(board.to_string(), "Soft_Job");
+        assert_eq!(board.known(), Some(BoardName::SoftJob));
+        assert_eq!(Board::from(BoardName::SoftJob), board);
+    }
+
+    #[test]
+    fn test_parse_invalid_board() {
+        assert!("".parse::<Board>().is_err());
+        assert!("Gossiping/../".parse::<Board>().is_err());
+        assert!("ThisNameIsTooLong".parse::<Board>().is_err());
+    }
+
+    #[test]
+    fn test_board_serde() {
+        let board = "Soft_Job".parse::<Board>().unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+
+        assert_eq!(json, "\"Soft_Job\"");
+        assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), board);
+        assert!(serde_json::from_str::<Board>("\"../\"").is_err());
+    }
+
+    #[test]
+    fn test_board_serde_of_board_name() {
+        let board = |json: &str| serde_json::from_str::<Board>(json).unwrap().to_string();
+
+        assert_eq!(board("\"SoftJob\""), "Soft_Job");
+        assert_eq!(board("\"CChat\""), "C_Chat");
+        assert_eq!(board("\"BankService\""), "Bank_Service");
+        assert_eq!(board("\"Gossiping\""), "Gossiping");
+        assert_eq!(board("\"Python\""), "Python");
+    }
+
+    #[test]
+    fn test_id_from_url() {
+        assert_eq!(
+            id_from_url("https://www.ptt.cc/bbs/Gossiping/M.1597463395.A.478.html"),
+            Some("M.1597463395.A.478")
+        );
+        assert_eq!(id_from_url("https://www.ptt.cc/bbs/Gossiping/"), None);
+    }
+
+    #[test]
+    fn test_timestamp_from_id() {
+        assert_eq!(timestamp_from_id("M.1597463395.A.478"), Some(1_597_463_395));
+        assert_eq!(timestamp_from_id("index"), None);
+    }
+
+    fn reply(content: &str) -> Reply {
+        Reply {
+            reply_type: ReplyType::Push,
+            author_id: "someone".to_owned(),
+            ip: None,
+            date: None,
+            content: content.to_owned(),
+            links: vec![],
+        }
+    }
+
+    fn article(replies: Vec<Reply>) -> Article {
+        Article {
+            reply_count: ReplyCount {
+                push: replies.len() as i16,
+                neutral: 0,
+                boo: 0,
+            },
+            replies,
+            ..test_article("M.1597463395.A.478")
+        }
+    }
+
+    #[test]
+    fn test_reply_count_since_reset() {
+        let mut article = article(vec![reply("1"), reply("2"), reply("3")]);
+        assert_eq!(article.reply_count_since_reset().push, 3);
+
+        article.moderation_events.push(ModerationEvent {
+            position: 2,
+            moderator_id: "someone".to_owned(),
+            date: None,
+            action: ModerationAction::ResetPushCount,
+        });
+        assert_eq!(article.reply_count_since_reset().push, 1);
+    }
+
+    #[test]
+    fn test_aid_conversion() {
+        assert_eq!(
+            id_from_aid("1VDrjZHu"),
+            Some("M.1597463395.A.478".to_owned())
+        );
+        assert_eq!(
+            aid_from_id("M.1597463395.A.478"),
+            Some("1VDrjZHu".to_owned())
+        );
+        assert_eq!(
+            id_from_aid(&aid_from_id("M.1175469904.A.05B").unwrap()),
+            Some("M.1175469904.A.05B".to_owned())
+        );
+        assert_eq!(id_from_aid("invalid!"), None);
+        assert_eq!(aid_from_id("index.html"), None);
+    }
+
+    #[test]
+    fn test_diff_appended_replies() {
+        let previous = article(vec![reply("1"), reply("2")]);
+        let refreshed = article(vec![reply("1"), reply("2"), reply("3")]);
+
+        let update = ReplyUpdate::diff(&previous, &refreshed);
+        assert_eq!(update.offset, 2);
+        assert_eq!(update.replies, vec![reply("3")]);
+        assert_eq!(update.reply_count.push, 3);
+        assert!(!update.is_empty(&previous));
+        assert!(ReplyUpdate::diff(&refreshed, &refreshed).is_empty(&refreshed));
+    }
+
+    #[test]
+    fn test_diff_removed_replies() {
+        let previous = article(vec![reply("1"), reply("2"), reply("3")]);
+        let refreshed = article(vec![reply("1"), reply("3")]);
+
+        let update = ReplyUpdate::diff(&previous, &refreshed);
+        assert_eq!(update.offset, 1);
+        assert_eq!(update.replies, vec![reply("3")]);
+        assert!(!update.is_empty(&previous));
+    }
+
+    #[test]
+    fn test_reply_update_ignores_links() {
+        let mut linked = reply("https://youtu.be/ACTGGln3SG0");
+        linked.links = vec![Link {
+            url: "https://youtu.be/ACTGGln3SG0".to_owned(),
+            kind: LinkKind::YouTube,
+        }];
+        let previous = article(vec![reply("1"), reply("https://youtu.be/ACTGGln3SG0")]);
+        let refreshed = article(vec![reply("1"), linked]);
+        let update = ReplyUpdate::diff(&previous, &refreshed);
+
+        assert_eq!(update.offset, 2);
+        assert!(update.is_empty(&previous));
+    }
+
+    #[test]
+    fn test_apply_reply_update() {
+        let mut previous = article(vec![reply("1"), reply("2"), reply("3")]);
+        let refreshed = article(vec![reply("1"), reply("3"), reply("4")]);
+
+        ReplyUpdate::diff(&previous, &refreshed).apply(&mut previous);
+        assert_eq!(previous.replies, refreshed.replies);
+        assert_eq!(previous.reply_count, refreshed.reply_count);
+    }
+}
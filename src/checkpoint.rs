@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use crate::article::{id_from_url, timestamp_from_id, Board};
+use crate::json_file::{load_json, save_json};
+
+/// Checkpoint stores the progress of crawling a board, so that an interrupted
+/// crawl can be resumed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    pub board: Board,
+    pub first_page: u32,
+    pub last_page: u32,
+    pub last_completed_page: Option<u32>,
+    pub completed_ids: BTreeSet<String>,
+    pub failed_urls: BTreeSet<String>,
+}
+
+impl Checkpoint {
+    pub fn new(board: Board, range: &RangeInclusive<u32>) -> Checkpoint {
+        Checkpoint {
+            board,
+            first_page: *range.start(),
+            last_page: *range.end(),
+            last_completed_page: None,
+            completed_ids: BTreeSet::new(),
+            failed_urls: BTreeSet::new(),
+        }
+    }
+
+    /// Load a Checkpoint from the JSON file at given path.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Checkpoint> {
+        load_json(path)
+    }
+
+    /// Save the Checkpoint as JSON file at given path.
+    /// The file is replaced only after the whole Checkpoint is written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        save_json(self, path)
+    }
+
+    /// Return the range of pages not completed yet, or None if all pages are completed.
+    pub fn remaining_range(&self) -> Option<RangeInclusive<u32>> {
+        let start = match self.last_completed_page {
+            Some(page) => page + 1,
+            None => self.first_page,
+        };
+        if start > self.last_page {
+            None
+        } else {
+            Some(start..=self.last_page)
+        }
+    }
+
+    /// Whether the article at given URL was crawled.
+    pub fn is_completed(&self, url: &str) -> bool {
+        match id_from_url(url) {
+            Some(id) => self.completed_ids.contains(id),
+            None => false,
+        }
+    }
+
+    /// Record the article at given URL as crawled.
+    pub fn complete_article(&mut self, url: &str) {
+        if let Some(id) = id_from_url(url) {
+            self.completed_ids.insert(id.to_owned());
+        }
+        self.failed_urls.remove(url);
+    }
+
+    /// Record the page or article at given URL as failed to crawl.
+    pub fn fail_url(&mut self, url: &str) {
+        self.failed_urls.insert(url.to_owned());
+    }
+
+    /// Record all articles in the page as processed. IDs of crawled articles
+    /// are dropped, since only those of the page being crawled are needed.
+    pub fn complete_page(&mut self, page: u32) {
+        self.last_completed_page = Some(page);
+        self.completed_ids.clear();
+    }
+}
+
+/// SeenState stores the IDs of articles already emitted for each watched
+/// board, so that they are not emitted again after restart.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SeenState {
+    pub boards: BTreeMap<Board, BTreeSet<String>>,
+}
+
+impl SeenState {
+    pub fn new() -> SeenState {
+        SeenState::default()
+    }
+
+    /// Load a SeenState from the JSON file at given path.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<SeenState> {
+        load_json(path)
+    }
+
+    /// Save the SeenState as JSON file at given path.
+    /// The file is replaced only after the whole SeenState is written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        save_json(self, path)
+    }
+
+    /// Return the IDs of articles seen in given board.
+    pub fn ids(&self, board: &Board) -> BTreeSet<String> {
+        self.boards.get(board).cloned().unwrap_or_default()
+    }
+
+    /// Record the article with given ID in given board as seen.
+    pub fn insert(&mut self, board: &Board, id: &str) {
+        self.boards
+            .entry(board.clone())
+            .or_default()
+            .insert(id.to_owned());
+    }
+
+    /// Keep only the newest `max_ids` IDs of each board, so that the state does
+    /// not grow forever.
+    pub fn prune(&mut self, max_ids: usize) {
+        for ids in self.boards.values_mut() {
+            if ids.len() <= max_ids {
+                continue;
+            }
+            let mut sorted: Vec<String> = ids.iter().cloned().collect();
+            sorted.sort_by_key(|id| timestamp_from_id(id));
+            *ids = sorted
+                .split_off(sorted.len() - max_ids)
+                .into_iter()
+                .collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::article::BoardName;
+
+    use super::*;
+
+    const URL: &str = "https://www.ptt.cc/bbs/Gossiping/M.1597463395.A.478.html";
+
+    #[test]
+    fn test_remaining_range() {
+        let mut checkpoint = Checkpoint::new(BoardName::Gossiping.into(), &(3..=5));
+        assert_eq!(checkpoint.remaining_range(), Some(3..=5));
+
+        checkpoint.complete_page(4);
+        assert_eq!(checkpoint.remaining_range(), Some(5..=5));
+
+        checkpoint.complete_page(5);
+        assert_eq!(checkpoint.remaining_range(), None);
+    }
+
+    #[test]
+    fn test_complete_article() {
+        let mut checkpoint = Checkpoint::new(BoardName::Gossiping.into(), &(1..=1));
+        checkpoint.fail_url(URL);
+        assert!(!checkpoint.is_completed(URL));
+
+        checkpoint.complete_article(URL);
+        assert!(checkpoint.is_completed(URL));
+        assert!(checkpoint.completed_ids.contains("M.1597463395.A.478"));
+        assert!(checkpoint.failed_urls.is_empty());
+    }
+
+    #[test]
+    fn test_complete_page() {
+        let mut checkpoint = Checkpoint::new(BoardName::Gossiping.into(), &(1..=2));
+        checkpoint.complete_article(URL);
+
+        checkpoint.complete_page(1);
+        assert!(!checkpoint.is_completed(URL));
+        assert!(checkpoint.completed_ids.is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = env::temp_dir().join("ptt_crawler_test_checkpoint.json");
+        let mut checkpoint = Checkpoint::new(BoardName::SoftJob.into(), &(1..=10));
+        checkpoint.complete_page(2);
+        checkpoint.complete_article(URL);
+        checkpoint.fail_url("https://www.ptt.cc/bbs/Soft_Job/index3.html");
+
+        checkpoint.save(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap(), checkpoint);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_seen_state_prune() {
+        let board: Board = BoardName::Gossiping.into();
+        let mut state = SeenState::new();
+        state.insert(&board, "M.1597463395.A.478");
+        state.insert(&board, "M.1597000000.A.001");
+        state.insert(&board, "M.1597463400.A.002");
+
+        state.prune(2);
+        assert_eq!(
+            state.ids(&board).into_iter().collect::<Vec<_>>(),
+            vec!["M.1597463395.A.478", "M.1597463400.A.002"]
+        );
+    }
+
+    #[test]
+    fn test_seen_state_save_and_load() {
+        let path = env::temp_dir().join("ptt_crawler_test_seen_state.json");
+        let mut state = SeenState::new();
+        state.insert(&BoardName::Gossiping.into(), "M.1597463395.A.478");
+        state.insert(&"Soft_Job".parse().unwrap(), "M.1597000000.A.001");
+
+        state.save(&path).unwrap();
+        assert_eq!(SeenState::load(&path).unwrap(), state);
+        fs::remove_file(&path).unwrap();
+    }
+}
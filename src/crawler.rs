@@ -1,14 +1,30 @@
 use std::boxed::Box;
+use std::collections::{HashMap, HashSet};
 use std::ops::RangeInclusive;
-use std::time::Duration;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
+use chrono::{DateTime, FixedOffset, Utc};
+use futures::future::{self, FutureExt, LocalBoxFuture};
+use futures::stream::{self, Stream, StreamExt};
 use regex::Regex;
-use reqwest::{header, redirect::Policy, Client, Proxy};
+use reqwest::{header, redirect::Policy, Proxy, RequestBuilder, Response};
 use select::document::Document;
 use select::predicate::{Class, Name, Predicate};
 use url::Url;
 
-use crate::{article::Article, article::BoardName, parser};
+use crate::{
+    article::{
+        self, Article, Board, BoardCategory, BoardEntry, BoardInfo, BoardListingEntry,
+        CategoryInfo, FeedEntry, ManListingEntry, ManNode, ReplyUpdate,
+    },
+    parser,
+    rate_limiter::RateLimiter,
+    retry::{self, RetryPolicy},
+    search::SearchQuery,
+    thread::{self, Thread},
+};
 
 const PTT_CC_URL: &str = "https://www.ptt.cc";
 
@@ -18,6 +34,8 @@ pub enum Error {
     ConnectionError(reqwest::Error),
     InvalidUrl,
     InvalidResponse,
+    /// Request still failed after the number of attempts, with the error of last attempt.
+    RetriesExhausted(u32, Box<Error>),
 }
 
 impl From<reqwest::Error> for Error {
@@ -26,6 +44,75 @@ impl From<reqwest::Error> for Error {
     }
 }
 
+/// LastSeen represents the newest article crawled previously.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LastSeen {
+    /// ID of the article, e.g. "M.1597463395.A.478"
+    Id(String),
+    /// Unix timestamp of the article creation
+    Timestamp(i64),
+}
+
+impl LastSeen {
+    /// Whether the article with given ID is newer than the last seen one.
+    /// Suffix of IDs is random, so other articles created in the same second
+    /// as the last seen one are taken as newer.
+    pub fn is_older_than(&self, id: &str) -> bool {
+        let timestamp = match article::timestamp_from_id(id) {
+            Some(t) => t,
+            None => return false,
+        };
+        match self {
+            LastSeen::Id(seen_id) => {
+                let seen_timestamp = article::timestamp_from_id(seen_id).unwrap_or_default();
+                timestamp > seen_timestamp || (timestamp == seen_timestamp && id != seen_id)
+            }
+            LastSeen::Timestamp(seen_timestamp) => timestamp > *seen_timestamp,
+        }
+    }
+}
+
+impl FromStr for LastSeen {
+    type Err = String;
+
+    /// Parse either an article ID or a Unix timestamp.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(timestamp) = s.parse::<i64>() {
+            return Ok(LastSeen::Timestamp(timestamp));
+        }
+        match article::timestamp_from_id(s) {
+            Some(_) => Ok(LastSeen::Id(s.to_owned())),
+            None => Err(format!("Invalid article ID or timestamp \"{}\"", s)),
+        }
+    }
+}
+
+/// Client is a HTTP client sending requests to PTT, limited by its own
+/// RateLimiter and retried by its own RetryPolicy.
+/// Clones of a Client share the same connection pool and RateLimiter.
+#[derive(Clone)]
+pub struct Client {
+    inner: reqwest::Client,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    retry_policy: RetryPolicy,
+}
+
+impl Client {
+    /// Limit the requests sent by this Client with given RateLimiter.
+    /// Pass None to send requests without any delay.
+    pub fn rate_limiter(mut self, limiter: Option<RateLimiter>) -> Client {
+        self.rate_limiter = limiter.map(Arc::new);
+        self
+    }
+
+    /// Retry the failed requests sent by this Client with given RetryPolicy.
+    /// Use `RetryPolicy::never()` to disable retrying.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Client {
+        self.retry_policy = policy;
+        self
+    }
+}
+
 /// Return a HTTP Client with cookie accepting over 18 agreement.
 /// One should reuse returned client as more as possible.
 pub async fn create_client(
@@ -52,19 +139,23 @@ pub async fn create_client(
     let url = format!("{}/ask/over18", PTT_CC_URL);
     let client = builder.build()?;
     match client.post(&url).form(&params).send().await {
-        Ok(_) => Ok(client),
+        Ok(_) => Ok(Client {
+            inner: client,
+            rate_limiter: None,
+            retry_policy: RetryPolicy::default(),
+        }),
         Err(e) => Err(Error::ConnectionError(e)),
     }
 }
 
 /// Crawl the page count of given board.
-pub async fn crawl_page_count(client: &Client, board: &BoardName) -> Result<u32, Error> {
+pub async fn crawl_page_count(client: &Client, board: &Board) -> Result<u32, Error> {
     lazy_static! {
         static ref RE: Regex = Regex::new(r"index(?P<num>\d+)").unwrap();
     }
 
     info!("Start crawling page count of board {}", board);
-    let latest_page_url = compose_page_url(&board, 0);
+    let latest_page_url = compose_page_url(board, 0);
     let document = transform_to_document(client, &latest_page_url, None).await?;
     let last_page_url = match document
         .find(Name("a").and(Class("wide")))
@@ -108,10 +199,13 @@ pub async fn crawl_url(
 }
 
 /// Given a board, crawls and returns the URLs of articles within range.
+/// At most `concurrency` pages are fetched at the same time, and the URLs are
+/// returned in the order of pages.
 pub async fn crawl_page_urls(
     client: &Client,
-    board: &BoardName,
+    board: &Board,
     range: &RangeInclusive<u32>,
+    concurrency: usize,
 ) -> Result<Vec<String>, Error> {
     info!(
         "Start crawling URLs of articles from board {} page {} to {}",
@@ -121,14 +215,11 @@ pub async fn crawl_page_urls(
     );
     let mut article_urls: Vec<String> = vec![];
     let mut error: Error = Error::InvalidResponse;
-    for page_num in range.clone() {
-        let page_url = compose_page_url(&board, page_num);
-        match crawl_one_page_urls(client, &page_url).await {
-            Ok(mut urls) => article_urls.append(&mut urls),
-            Err(e) => {
-                error!("{:?} occurred when crawling {}", e, page_url);
-                error = e;
-            }
+    let mut results = crawl_page_urls_stream(client, board, range, concurrency).boxed_local();
+    while let Some(result) = results.next().await {
+        match result {
+            Ok(url) => article_urls.push(url),
+            Err(e) => error = e,
         };
     }
 
@@ -145,11 +236,39 @@ pub async fn crawl_page_urls(
     Ok(article_urls)
 }
 
+/// Given a board, returns a Stream yielding the URLs of articles within range
+/// as soon as each page is crawled.
+/// At most `concurrency` pages are fetched at the same time, and the URLs are
+/// yielded in the order of pages. A page failed to crawl yields an error.
+pub fn crawl_page_urls_stream<'a>(
+    client: &'a Client,
+    board: &'a Board,
+    range: &RangeInclusive<u32>,
+    concurrency: usize,
+) -> impl Stream<Item = Result<String, Error>> + 'a {
+    stream::iter(range.clone())
+        .map(move |page_num| async move {
+            let page_url = compose_page_url(board, page_num);
+            match crawl_one_page_urls(client, &page_url).await {
+                Ok(urls) => urls.into_iter().map(Ok).collect(),
+                Err(e) => {
+                    error!("{:?} occurred when crawling {}", e, page_url);
+                    vec![Err(e)]
+                }
+            }
+        })
+        .buffered(concurrency.max(1))
+        .flat_map(stream::iter)
+}
+
 /// Given a board, crawls and returns parsed Articles within range.
+/// At most `concurrency` pages or articles are fetched at the same time, and
+/// the Articles are returned in the order of pages and articles.
 pub async fn crawl_page_articles(
     client: &Client,
-    board: &BoardName,
+    board: &Board,
     range: &RangeInclusive<u32>,
+    concurrency: usize,
 ) -> Result<Vec<Article>, Error> {
     info!(
         "Start crawling articles from board {} page {} to {}",
@@ -159,14 +278,11 @@ pub async fn crawl_page_articles(
     );
     let mut articles: Vec<Article> = vec![];
     let mut error: Error = Error::InvalidResponse;
-    let article_urls = crawl_page_urls(client, board, range).await?;
-    for url in article_urls {
-        match crawl_url(client, &url, None).await {
+    let mut results = crawl_page_articles_stream(client, board, range, concurrency).boxed_local();
+    while let Some(result) = results.next().await {
+        match result {
             Ok(article) => articles.push(article),
-            Err(e) => {
-                error!("{:?} occurred when crawling {:?}", e, url);
-                error = e;
-            }
+            Err(e) => error = e,
         }
     }
 
@@ -183,15 +299,304 @@ pub async fn crawl_page_articles(
     Ok(articles)
 }
 
+/// Given a board, returns a Stream yielding parsed Articles within range as
+/// soon as each of them is crawled.
+/// At most `concurrency` pages or articles are fetched at the same time, and
+/// the Articles are yielded in the order of pages and articles. A page or an
+/// article failed to crawl yields an error.
+pub fn crawl_page_articles_stream<'a>(
+    client: &'a Client,
+    board: &'a Board,
+    range: &RangeInclusive<u32>,
+    concurrency: usize,
+) -> impl Stream<Item = Result<Article, Error>> + 'a {
+    // Pages are fetched one by one, and `buffered` polls the next URL only when
+    // fewer than `concurrency` articles are being fetched. Thus a page is fetched
+    // only with free slot of articles, and the total never exceeds `concurrency`.
+    crawl_page_urls_stream(client, board, range, 1)
+        .map(move |result| async move {
+            let url = result?;
+            crawl_url(client, &url, None).await.map_err(|e| {
+                error!("{:?} occurred when crawling {:?}", e, url);
+                e
+            })
+        })
+        .buffered(concurrency.max(1))
+}
+
+/// Given a board, crawls and returns the URLs of articles newer than the last
+/// seen one, in the order from oldest to newest.
+/// Index pages are crawled backwards from the latest page, until a page
+/// starting with an article which is not newer.
+pub async fn crawl_new_urls(
+    client: &Client,
+    board: &Board,
+    last_seen: &LastSeen,
+) -> Result<Vec<String>, Error> {
+    info!(
+        "Start crawling URLs of articles from board {} newer than {:?}",
+        board, last_seen
+    );
+    let page_count = crawl_page_count(client, board).await?;
+    let mut pages_of_urls: Vec<Vec<String>> = vec![];
+    // articles shifted to next page while crawling are listed twice
+    let mut ids: HashSet<String> = HashSet::new();
+    for page_num in (1..=page_count).rev() {
+        let page_url = compose_page_url(board, page_num);
+        let urls = crawl_one_page_urls(client, &page_url).await?;
+        let is_last_page = match urls.first().and_then(|u| article::id_from_url(u)) {
+            Some(id) => !last_seen.is_older_than(id),
+            None => false,
+        };
+        pages_of_urls.push(
+            urls.into_iter()
+                .filter(|u| match article::id_from_url(u) {
+                    Some(id) => last_seen.is_older_than(id) && ids.insert(id.to_owned()),
+                    None => false,
+                })
+                .collect(),
+        );
+        if is_last_page {
+            break;
+        }
+    }
+
+    let new_urls: Vec<String> = pages_of_urls.into_iter().rev().flatten().collect();
+    info!(
+        "Finish crawling URLs of articles from board {} newer than {:?}. count: {}",
+        board,
+        last_seen,
+        new_urls.len()
+    );
+    Ok(new_urls)
+}
+
+/// Given a board, crawls and returns parsed Articles newer than the last seen
+/// one, in the order from oldest to newest.
+/// At most `concurrency` articles are fetched at the same time.
+pub async fn crawl_new_articles(
+    client: &Client,
+    board: &Board,
+    last_seen: &LastSeen,
+    concurrency: usize,
+) -> Result<Vec<Article>, Error> {
+    let urls = crawl_new_urls(client, board, last_seen).await?;
+    crawl_articles(client, urls, concurrency).await
+}
+
+/// Given a board, finds the range of index pages containing articles created
+/// within `since..until` by binary searching the timestamps in article IDs.
+/// Returns None if no page contains such articles.
+pub async fn crawl_page_range_by_date(
+    client: &Client,
+    board: &Board,
+    since: DateTime<FixedOffset>,
+    until: DateTime<FixedOffset>,
+) -> Result<Option<RangeInclusive<u32>>, Error> {
+    info!(
+        "Start finding pages of board {} from {} to {}",
+        board, since, until
+    );
+    let page_count = crawl_page_count(client, board).await?;
+    let mut page_timestamps = HashMap::new();
+    let start_page = find_last_page_before(
+        client,
+        board,
+        page_count,
+        since.timestamp(),
+        &mut page_timestamps,
+    )
+    .await?
+    .unwrap_or(1);
+    // the last page starting before `until` is the last page containing articles before it
+    let end_page = find_last_page_before(
+        client,
+        board,
+        page_count,
+        until.timestamp() - 1,
+        &mut page_timestamps,
+    )
+    .await?;
+
+    let range = match end_page {
+        Some(end_page) if end_page >= start_page => Some(start_page..=end_page),
+        _ => None,
+    };
+    info!(
+        "Finish finding pages of board {} from {} to {}. range: {:?}",
+        board, since, until, range
+    );
+    Ok(range)
+}
+
+/// Given a board, returns a Stream yielding parsed Articles created within
+/// `since..until`, as soon as each of them is crawled.
+/// At most `concurrency` pages or articles are fetched at the same time.
+pub async fn crawl_date_range_articles_stream<'a>(
+    client: &'a Client,
+    board: &'a Board,
+    since: DateTime<FixedOffset>,
+    until: DateTime<FixedOffset>,
+    concurrency: usize,
+) -> Result<impl Stream<Item = Result<Article, Error>> + 'a, Error> {
+    let range = crawl_page_range_by_date(client, board, since, until)
+        .await?
+        // an empty range when no page is found
+        .unwrap_or(RangeInclusive::new(1, 0));
+    Ok(
+        crawl_page_articles_stream(client, board, &range, concurrency).filter(move |result| {
+            let is_within = match result {
+                Ok(article) => match article.meta.date {
+                    Some(date) => date >= since && date < until,
+                    None => false,
+                },
+                Err(_) => true,
+            };
+            future::ready(is_within)
+        }),
+    )
+}
+
+/// Given a board, crawls and returns parsed Articles created within `since..until`.
+/// Articles failed to crawl are skipped, unless none of them is crawled.
+/// At most `concurrency` pages or articles are fetched at the same time.
+pub async fn crawl_date_range_articles(
+    client: &Client,
+    board: &Board,
+    since: DateTime<FixedOffset>,
+    until: DateTime<FixedOffset>,
+    concurrency: usize,
+) -> Result<Vec<Article>, Error> {
+    let mut articles: Vec<Article> = vec![];
+    let mut error: Option<Error> = None;
+    let mut results = crawl_date_range_articles_stream(client, board, since, until, concurrency)
+        .await?
+        .boxed_local();
+    while let Some(result) = results.next().await {
+        match result {
+            Ok(article) => articles.push(article),
+            Err(e) => error = Some(e),
+        }
+    }
+
+    match error {
+        Some(e) if articles.is_empty() => {
+            error!("No article was found");
+            Err(e)
+        }
+        _ => Ok(articles),
+    }
+}
+
+/// Returns the last page whose first article was created at or before
+/// `timestamp`, or None if all pages start after it.
+async fn find_last_page_before(
+    client: &Client,
+    board: &Board,
+    page_count: u32,
+    timestamp: i64,
+    page_timestamps: &mut HashMap<u32, i64>,
+) -> Result<Option<u32>, Error> {
+    let mut low = 1;
+    let mut high = page_count;
+    let mut found = None;
+    while low <= high {
+        let middle = low + (high - low) / 2;
+        if crawl_page_timestamp(client, board, page_count, middle, page_timestamps).await?
+            <= timestamp
+        {
+            found = Some(middle);
+            low = middle + 1;
+        } else {
+            high = middle - 1;
+        }
+    }
+    Ok(found)
+}
+
+/// Returns the creation timestamp of first article in the page. Pages without
+/// any article take the timestamp of the next page.
+async fn crawl_page_timestamp(
+    client: &Client,
+    board: &Board,
+    page_count: u32,
+    page: u32,
+    page_timestamps: &mut HashMap<u32, i64>,
+) -> Result<i64, Error> {
+    for page_num in page..=page_count {
+        if let Some(timestamp) = page_timestamps.get(&page_num) {
+            return Ok(*timestamp);
+        }
+        let urls = crawl_one_page_urls(client, &compose_page_url(board, page_num)).await?;
+        let timestamp = urls
+            .iter()
+            .filter_map(|u| article::id_from_url(u).and_then(article::timestamp_from_id))
+            .next();
+        if let Some(timestamp) = timestamp {
+            page_timestamps.insert(page_num, timestamp);
+            return Ok(timestamp);
+        }
+    }
+    Ok(i64::MAX)
+}
+
+/// Returns a Stream yielding parsed Articles of given URLs as soon as each of
+/// them is crawled.
+/// At most `concurrency` articles are fetched at the same time, and the
+/// Articles are yielded in the order of URLs.
+pub fn crawl_urls_stream(
+    client: &Client,
+    urls: Vec<String>,
+    concurrency: usize,
+) -> impl Stream<Item = (String, Result<Article, Error>)> + '_ {
+    stream::iter(urls)
+        .map(move |url| async move {
+            let result = crawl_url(client, &url, None).await;
+            if let Err(e) = &result {
+                error!("{:?} occurred when crawling {:?}", e, url);
+            }
+            (url, result)
+        })
+        .buffered(concurrency.max(1))
+}
+
+/// Crawls and returns parsed Articles of given URLs, in the order of URLs.
+/// Articles failed to crawl are skipped, unless none of them is crawled.
+/// At most `concurrency` articles are fetched at the same time.
+async fn crawl_articles(
+    client: &Client,
+    urls: Vec<String>,
+    concurrency: usize,
+) -> Result<Vec<Article>, Error> {
+    let mut articles: Vec<Article> = vec![];
+    let mut error: Option<Error> = None;
+    let mut results = crawl_urls_stream(client, urls, concurrency).boxed_local();
+    while let Some((_, result)) = results.next().await {
+        match result {
+            Ok(article) => articles.push(article),
+            Err(e) => error = Some(e),
+        }
+    }
+
+    match error {
+        Some(e) if articles.is_empty() => {
+            error!("No article was found");
+            Err(e)
+        }
+        _ => Ok(articles),
+    }
+}
+
 fn is_supported_url(url: &str) -> bool {
     if !url.contains(PTT_CC_URL) {
         return false;
     }
 
-    let ptt_cc_url_valid_path: Vec<Box<dyn Fn(&str) -> bool>> = {
+    type PathPredicate = Box<dyn Fn(&str) -> bool>;
+    let ptt_cc_url_valid_path: Vec<PathPredicate> = {
         vec![
-            Box::new(move |s| s == "bbs"),
-            Box::new(move |s| s.to_owned().parse::<BoardName>().is_ok()),
+            Box::new(move |s| s == "bbs" || s == "man"),
+            Box::new(move |s| s.parse::<Board>().is_ok()),
         ]
     };
 
@@ -210,64 +615,694 @@ async fn transform_to_document(
     url: &str,
     user_agent: Option<String>,
 ) -> Result<Document, Error> {
-    let mut request = client.get(url);
-    if let Some(ua) = user_agent {
-        request = request.header(header::USER_AGENT, ua);
+    let response = send_with_retry(client, url, |c| {
+        let mut request = c.get(url);
+        if let Some(ua) = &user_agent {
+            request = request.header(header::USER_AGENT, ua.as_str());
+        }
+        request
+    })
+    .await?;
+    if !response.status().is_success() {
+        return Err(Error::InvalidResponse);
     }
+    match response.text().await {
+        Ok(t) => Ok(Document::from(t.as_str())),
+        Err(_) => Err(Error::InvalidResponse),
+    }
+}
 
-    let text_future = match request.send().await {
-        Ok(r) => {
-            if !r.status().is_success() {
-                return Err(Error::InvalidResponse);
+/// Send the request built by `build_request` until it succeeds or the
+/// RetryPolicy gives up. Responses with status not to be retried are returned
+/// as is.
+async fn send_with_retry<F>(client: &Client, url: &str, build_request: F) -> Result<Response, Error>
+where
+    F: Fn(&reqwest::Client) -> RequestBuilder,
+{
+    let policy = &client.retry_policy;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        if let Some(limiter) = &client.rate_limiter {
+            limiter.acquire().await;
+        }
+        let (error, retryable, retry_after) = match build_request(&client.inner).send().await {
+            Ok(r) => {
+                let status = r.status();
+                if !policy.is_retryable_status(status) {
+                    return Ok(r);
+                }
+                let retry_after = r
+                    .headers()
+                    .get(header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| retry::parse_retry_after(v, SystemTime::now()));
+                warn!("Status {} responded when requesting to {}", status, url);
+                (Error::InvalidResponse, true, retry_after)
             }
-            r.text()
+            Err(e) => {
+                if e.is_timeout() {
+                    error!("Timeout occurred when requesting to {}", url);
+                }
+                let retryable = policy.is_retryable_error(&e);
+                (Error::ConnectionError(e), retryable, None)
+            }
+        };
+
+        if !retryable || attempt >= policy.max_attempts {
+            if attempt > 1 {
+                error!(
+                    "Failed to request to {} after {} attempts. Last error: {:?}",
+                    url, attempt, error
+                );
+                return Err(Error::RetriesExhausted(attempt, Box::new(error)));
+            }
+            return Err(error);
         }
-        Err(e) => {
-            if e.is_timeout() {
-                error!("Timeout occurred when requesting to {}", url);
+        let delay = policy.delay(attempt, retry_after);
+        warn!(
+            "Attempt {}/{} to request to {} failed, retry in {:?}",
+            attempt, policy.max_attempts, url, delay
+        );
+        tokio::time::delay_for(delay).await;
+    }
+}
+
+/// Return the URL of index page of given board. Page 0 is the latest page.
+pub fn compose_page_url(board: &Board, page: u32) -> String {
+    format!("{}/bbs/{}/index{}.html", PTT_CC_URL, board, page)
+}
+
+/// Return the URL of article with given ID in given board.
+pub fn compose_article_url(board: &Board, id: &str) -> String {
+    format!("{}/bbs/{}/{}.html", PTT_CC_URL, board, id)
+}
+
+/// Given a URL of index page, crawls and returns the URLs of articles in it.
+pub async fn crawl_one_page_urls(client: &Client, url: &str) -> Result<Vec<String>, Error> {
+    info!("Start crawling article URLs in page {}", url);
+    let article_urls = crawl_one_page_listing(client, url)
+        .await?
+        .into_iter()
+        .flat_map(|entry| entry.url)
+        .collect();
+    info!("Finish crawling article URLs in page {}", url);
+    Ok(article_urls)
+}
+
+/// Given a board, crawls and returns the entries of articles listed in index
+/// pages within range.
+/// At most `concurrency` pages are fetched at the same time, and the entries
+/// are returned in the order of pages.
+pub async fn crawl_page_listing(
+    client: &Client,
+    board: &Board,
+    range: &RangeInclusive<u32>,
+    concurrency: usize,
+) -> Result<Vec<BoardListingEntry>, Error> {
+    info!(
+        "Start crawling listing from board {} page {} to {}",
+        board,
+        range.start(),
+        range.end()
+    );
+    let mut entries: Vec<BoardListingEntry> = vec![];
+    let mut error: Error = Error::InvalidResponse;
+    let mut results = stream::iter(range.clone())
+        .map(|page_num| async move {
+            let page_url = compose_page_url(board, page_num);
+            let result = crawl_one_page_listing(client, &page_url).await;
+            (page_url, result)
+        })
+        .buffered(concurrency.max(1));
+    while let Some((page_url, result)) = results.next().await {
+        match result {
+            Ok(mut page_entries) => entries.append(&mut page_entries),
+            Err(e) => {
+                error!("{:?} occurred when crawling {}", e, page_url);
+                error = e;
             }
-            return Err(Error::ConnectionError(e));
         }
-    };
-    match text_future.await {
-        Ok(t) => Ok(Document::from(t.as_str())),
-        Err(_) => Err(Error::InvalidResponse),
     }
+
+    info!(
+        "Finish crawling listing from board {} page {} to {}",
+        board,
+        range.start(),
+        range.end()
+    );
+    if entries.is_empty() {
+        error!("No entry was found");
+        return Err(error);
+    }
+    Ok(entries)
+}
+
+/// Given a URL of index page, crawls and returns the entries of articles listed in it.
+pub async fn crawl_one_page_listing(
+    client: &Client,
+    url: &str,
+) -> Result<Vec<BoardListingEntry>, Error> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"index(?P<num>\d+)\.html").unwrap();
+    }
+
+    let page = RE
+        .captures(url)
+        .and_then(|cap| cap["num"].parse::<u32>().ok());
+    let document = transform_to_document(client, url, None).await?;
+    Ok(parser::parse_listing(&document, PTT_CC_URL, page))
+}
+
+/// Return the URL of given page of search results in given board. Page 1 is
+/// the latest page.
+pub fn compose_search_url(board: &Board, query: &SearchQuery, page: u32) -> String {
+    let base = format!("{}/bbs/{}/search", PTT_CC_URL, board);
+    let page = page.to_string();
+    let query = query.to_string();
+    Url::parse_with_params(&base, &[("page", page.as_str()), ("q", query.as_str())])
+        .map(String::from)
+        .unwrap_or(base)
 }
 
-fn compose_page_url(board: &BoardName, page: u32) -> String {
-    format!(
-        "{}/bbs/{}/index{}.html",
-        PTT_CC_URL,
-        board.to_string(),
-        page
+/// Given a board, crawls and returns the entries of articles matching the query.
+/// Pages of search results are crawled from the latest one until no more
+/// entry is found or `max_pages` pages are crawled.
+pub async fn crawl_search(
+    client: &Client,
+    board: &Board,
+    query: &SearchQuery,
+    max_pages: Option<u32>,
+) -> Result<Vec<BoardListingEntry>, Error> {
+    info!("Start searching \"{}\" in board {}", query, board);
+    let mut entries: Vec<BoardListingEntry> = vec![];
+    let mut page_num = 1;
+    while page_num <= max_pages.unwrap_or(u32::MAX) {
+        let page_url = compose_search_url(board, query, page_num);
+        let mut page_entries = match crawl_one_page_listing(client, &page_url).await {
+            Ok(page_entries) => page_entries,
+            // PTT responds 404 for pages beyond the last one
+            Err(Error::InvalidResponse) if page_num > 1 => break,
+            Err(e) => {
+                error!("{:?} occurred when crawling {}", e, page_url);
+                return Err(e);
+            }
+        };
+        if page_entries.is_empty() {
+            break;
+        }
+        entries.append(&mut page_entries);
+        page_num += 1;
+    }
+    info!(
+        "Finish searching \"{}\" in board {}, {} entries found",
+        query,
+        board,
+        entries.len()
+    );
+    Ok(entries)
+}
+
+/// Given a board, crawls and returns parsed Articles matching the query.
+/// At most `concurrency` articles are fetched at the same time, and the
+/// Articles are returned in the order of search results. Articles failed to
+/// crawl are skipped, unless none of them is crawled.
+pub async fn crawl_search_articles(
+    client: &Client,
+    board: &Board,
+    query: &SearchQuery,
+    max_pages: Option<u32>,
+    concurrency: usize,
+) -> Result<Vec<Article>, Error> {
+    let urls = crawl_search(client, board, query, max_pages)
+        .await?
+        .into_iter()
+        .flat_map(|entry| entry.url)
+        .collect();
+    crawl_articles(client, urls, concurrency).await
+}
+
+/// Given an author ID, crawls and returns parsed Articles posted by the author
+/// in given boards, by searching each board with `author:` operator.
+/// Articles are sorted by date from the oldest one, and articles with the same
+/// ID, e.g. cross-posted ones, are returned only once. Articles failed to crawl
+/// are skipped, unless none of them is crawled.
+pub async fn crawl_author(
+    client: &Client,
+    author_id: &str,
+    boards: &[Board],
+    max_pages: Option<u32>,
+    concurrency: usize,
+) -> Result<Vec<Article>, Error> {
+    info!("Start crawling articles of author {}", author_id);
+    let query = SearchQuery::new().author(author_id);
+    let mut board_urls: Vec<Vec<String>> = vec![];
+    let mut error: Error = Error::InvalidResponse;
+    for board in boards {
+        match crawl_search(client, board, &query, max_pages).await {
+            Ok(entries) => board_urls.push(entries.into_iter().flat_map(|e| e.url).collect()),
+            Err(e) => {
+                error!("{:?} occurred when searching board {}", e, board);
+                error = e;
+            }
+        }
+    }
+    if board_urls.is_empty() && !boards.is_empty() {
+        return Err(error);
+    }
+
+    let urls = merge_article_urls(board_urls);
+    let mut articles = crawl_articles(client, urls, concurrency).await?;
+    articles.sort_by_key(|a| a.meta.timestamp());
+    info!(
+        "Finish crawling articles of author {}, {} articles found",
+        author_id,
+        articles.len()
+    );
+    Ok(articles)
+}
+
+/// Merge URLs of articles from different boards into one list sorted by the
+/// creation time in their IDs. URLs of articles with the same ID are kept once.
+fn merge_article_urls(board_urls: Vec<Vec<String>>) -> Vec<String> {
+    let mut seen_ids = HashSet::new();
+    let mut urls: Vec<String> = board_urls
+        .into_iter()
+        .flatten()
+        .filter(|url| match article::id_from_url(url) {
+            Some(id) => seen_ids.insert(id.to_owned()),
+            None => false,
+        })
+        .collect();
+    urls.sort_by_key(|url| article::id_from_url(url).and_then(article::timestamp_from_id));
+    urls
+}
+
+/// Given a board and a title of article, crawls and returns the Thread of
+/// articles with the same title found by `thread:` search.
+pub async fn crawl_thread(
+    client: &Client,
+    board: &Board,
+    title: &str,
+    max_pages: Option<u32>,
+    concurrency: usize,
+) -> Result<Thread, Error> {
+    let title = thread::normalize_title(title);
+    let query = SearchQuery::new().thread(title.as_str());
+    let articles = crawl_search_articles(client, board, &query, max_pages, concurrency).await?;
+    Ok(Thread::new(board.clone(), title, articles))
+}
+
+/// ManTask represents a pending step of traversing the digest of board.
+enum ManTask {
+    /// Yield the directory, then crawl its entries
+    Directory {
+        path: Vec<String>,
+        url: String,
+    },
+    /// Crawl the entries of directory
+    Expand {
+        path: Vec<String>,
+        url: String,
+    },
+    Article {
+        path: Vec<String>,
+        url: String,
+    },
+}
+
+/// Given a board, returns a Stream yielding directories and parsed Articles in
+/// the digest (精華區) of board, traversed in depth-first order.
+/// Entries of directories deeper than `max_depth` are not crawled. Directories
+/// in the root of digest have depth 1.
+pub fn crawl_man_stream<'a>(
+    client: &'a Client,
+    board: &Board,
+    max_depth: u32,
+) -> impl Stream<Item = Result<ManNode, Error>> + 'a {
+    let root = ManTask::Expand {
+        path: vec![],
+        url: format!("{}/man/{}/index.html", PTT_CC_URL, board),
+    };
+    stream::unfold(
+        (vec![root], HashSet::new()),
+        move |(mut tasks, mut visited_urls)| async move {
+            while let Some(task) = tasks.pop() {
+                let node = match task {
+                    ManTask::Directory { path, url } => {
+                        if path.len() < max_depth as usize {
+                            tasks.push(ManTask::Expand {
+                                path: path.clone(),
+                                url: url.clone(),
+                            });
+                        }
+                        Ok(ManNode::Directory { path, url })
+                    }
+                    ManTask::Expand { path, url } => {
+                        if !visited_urls.insert(url.clone()) {
+                            continue;
+                        }
+                        match crawl_man_entries(client, &url).await {
+                            Ok(entries) => {
+                                // push in reverse so that entries are popped in order
+                                for entry in entries.into_iter().rev() {
+                                    let mut entry_path = path.clone();
+                                    entry_path.push(entry.title);
+                                    tasks.push(if entry.is_directory {
+                                        ManTask::Directory {
+                                            path: entry_path,
+                                            url: entry.url,
+                                        }
+                                    } else {
+                                        ManTask::Article {
+                                            path: entry_path,
+                                            url: entry.url,
+                                        }
+                                    });
+                                }
+                                continue;
+                            }
+                            Err(e) => {
+                                error!("{:?} occurred when crawling {}", e, url);
+                                Err(e)
+                            }
+                        }
+                    }
+                    ManTask::Article { path, url } => match crawl_url(client, &url, None).await {
+                        Ok(article) => Ok(ManNode::Article {
+                            path,
+                            url,
+                            article: Box::new(article),
+                        }),
+                        Err(e) => {
+                            error!("{:?} occurred when crawling {}", e, url);
+                            Err(e)
+                        }
+                    },
+                };
+                return Some((node, (tasks, visited_urls)));
+            }
+            None
+        },
     )
 }
 
-async fn crawl_one_page_urls(client: &Client, url: &str) -> Result<Vec<String>, Error> {
-    info!("Start crawling article URLs in page {}", url);
+/// Given a URL of directory in digest, crawls and returns the entries listed in it.
+pub async fn crawl_man_entries(client: &Client, url: &str) -> Result<Vec<ManListingEntry>, Error> {
     let document = transform_to_document(client, url, None).await?;
-    let article_urls = document
-        .find(Class("title"))
-        .flat_map(|n| {
-            n.children().find(|n| {
-                let title = n.text();
-                n.name() == Some("a") && !title.trim().is_empty()
-            })
+    Ok(parser::parse_man_entries(&document, PTT_CC_URL))
+}
+
+/// Given a board, crawls and returns the entries in its Atom feed, which lists
+/// the newest articles of board.
+pub async fn crawl_feed(client: &Client, board: &Board) -> Result<Vec<FeedEntry>, Error> {
+    info!("Start crawling feed of board {}", board);
+    let url = format!("{}/atom/{}.xml", PTT_CC_URL, board);
+    let document = transform_to_document(client, &url, None).await?;
+    let entries = parser::parse_feed(&document);
+    info!(
+        "Finish crawling feed of board {}, {} entries found",
+        board,
+        entries.len()
+    );
+    Ok(entries)
+}
+
+/// Given a board, crawls its Atom feed and returns parsed Articles of entries
+/// whose IDs are not in `seen_ids`.
+/// At most `concurrency` articles are fetched at the same time, and the
+/// Articles are returned from the oldest one. Articles failed to crawl are
+/// skipped, unless none of them is crawled.
+pub async fn crawl_feed_articles(
+    client: &Client,
+    board: &Board,
+    seen_ids: &HashSet<String>,
+    concurrency: usize,
+) -> Result<Vec<Article>, Error> {
+    let urls = unseen_feed_urls(crawl_feed(client, board).await?, seen_ids);
+    crawl_articles(client, urls, concurrency).await
+}
+
+/// Given a board, crawls its Atom feed, or the latest index page if the feed
+/// failed, and returns the URLs of articles whose IDs are not in `seen_ids`,
+/// sorted from the oldest one.
+pub async fn crawl_unseen_urls(
+    client: &Client,
+    board: &Board,
+    seen_ids: &HashSet<String>,
+) -> Result<Vec<String>, Error> {
+    match crawl_feed(client, board).await {
+        Ok(entries) => Ok(unseen_feed_urls(entries, seen_ids)),
+        Err(e) => {
+            warn!(
+                "{:?} occurred when crawling feed of board {}, crawl latest page instead",
+                e, board
+            );
+            let entries = crawl_one_page_listing(client, &compose_page_url(board, 0)).await?;
+            Ok(entries
+                .into_iter()
+                .filter(|entry| !entry.pinned)
+                .flat_map(|entry| entry.url)
+                .filter(|url| match article::id_from_url(url) {
+                    Some(id) => !seen_ids.contains(id),
+                    None => false,
+                })
+                .collect())
+        }
+    }
+}
+
+/// Return the URLs of feed entries whose IDs are not in `seen_ids`, sorted
+/// from the oldest one.
+fn unseen_feed_urls(mut entries: Vec<FeedEntry>, seen_ids: &HashSet<String>) -> Vec<String> {
+    entries.retain(|e| !seen_ids.contains(&e.id));
+    entries.sort_by_key(|e| article::timestamp_from_id(&e.id));
+    entries.into_iter().map(|e| e.url).collect()
+}
+
+/// Given previously crawled Articles, returns a Stream yielding the replies
+/// changed since then for articles posted within `max_age`.
+/// Articles without any change are skipped. At most `concurrency` articles are
+/// fetched at the same time.
+pub fn crawl_reply_updates_stream(
+    client: &Client,
+    articles: Vec<Article>,
+    max_age: chrono::Duration,
+    concurrency: usize,
+) -> impl Stream<Item = Result<ReplyUpdate, Error>> + '_ {
+    let articles = filter_younger_than(articles, max_age, Utc::now().timestamp());
+    stream::iter(articles)
+        .map(move |previous| async move {
+            let url = compose_article_url(&previous.meta.board, &previous.meta.id);
+            match crawl_url(client, &url, None).await {
+                Ok(refreshed) => {
+                    let update = ReplyUpdate::diff(&previous, &refreshed);
+                    if update.is_empty(&previous) {
+                        None
+                    } else {
+                        Some(Ok(update))
+                    }
+                }
+                Err(e) => {
+                    error!("{:?} occurred when crawling {:?}", e, url);
+                    Some(Err(e))
+                }
+            }
         })
-        .map(|a| {
-            let relative_path = a.attr("href").unwrap().to_owned();
-            format!("{}{}", PTT_CC_URL, relative_path)
+        .buffered(concurrency.max(1))
+        .filter_map(future::ready)
+}
+
+/// Return the articles posted within `max_age` before `now`, which is a Unix timestamp.
+fn filter_younger_than(
+    articles: Vec<Article>,
+    max_age: chrono::Duration,
+    now: i64,
+) -> Vec<Article> {
+    let since = now - max_age.num_seconds();
+    articles
+        .into_iter()
+        .filter(|a| matches!(a.meta.timestamp(), Some(t) if t >= since))
+        .collect()
+}
+
+/// Crawls and returns the boards listed in hot boards page.
+pub async fn crawl_hot_boards(client: &Client) -> Result<Vec<BoardInfo>, Error> {
+    info!("Start crawling hot boards");
+    let url = format!("{}/bbs/hotboards.html", PTT_CC_URL);
+    let document = transform_to_document(client, &url, None).await?;
+    let boards = parser::parse_board_entries(&document, PTT_CC_URL)
+        .into_iter()
+        .flat_map(|entry| match entry {
+            BoardEntry::Board(board) => Some(board),
+            BoardEntry::Category(_) => None,
         })
         .collect();
-    info!("Finish crawling article URLs in page {}", url);
-    Ok(article_urls)
+    info!("Finish crawling hot boards");
+    Ok(boards)
+}
+
+/// Crawls the classification tree of boards from the root category.
+/// Categories deeper than `max_depth` are listed without their boards and
+/// sub-categories. The root category has depth 0.
+pub async fn crawl_board_tree(client: &Client, max_depth: u32) -> Result<BoardCategory, Error> {
+    info!("Start crawling classification tree of boards");
+    let root = CategoryInfo {
+        name: "".to_owned(),
+        title: "".to_owned(),
+        url: format!("{}/cls/1", PTT_CC_URL),
+    };
+    let mut visited_urls = HashSet::new();
+    let tree = crawl_category(client, root, 0, max_depth, &mut visited_urls).await?;
+    info!("Finish crawling classification tree of boards");
+    Ok(tree)
+}
+
+fn crawl_category<'a>(
+    client: &'a Client,
+    info: CategoryInfo,
+    depth: u32,
+    max_depth: u32,
+    visited_urls: &'a mut HashSet<String>,
+) -> LocalBoxFuture<'a, Result<BoardCategory, Error>> {
+    async move {
+        let mut category = BoardCategory {
+            info,
+            boards: vec![],
+            categories: vec![],
+        };
+        if depth > max_depth || !visited_urls.insert(category.info.url.clone()) {
+            return Ok(category);
+        }
+
+        let document = transform_to_document(client, &category.info.url, None).await?;
+        let entries = parser::parse_board_entries(&document, PTT_CC_URL);
+        for entry in entries {
+            match entry {
+                BoardEntry::Board(board) => category.boards.push(board),
+                BoardEntry::Category(sub_info) => {
+                    let url = sub_info.url.clone();
+                    match crawl_category(client, sub_info, depth + 1, max_depth, visited_urls).await
+                    {
+                        Ok(sub_category) => category.categories.push(sub_category),
+                        Err(e) => error!("{:?} occurred when crawling {}", e, url),
+                    }
+                }
+            }
+        }
+        Ok(category)
+    }
+    .boxed_local()
 }
 
 #[cfg(test)]
+#[allow(clippy::collapsible_match, clippy::match_like_matches_macro)]
 mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::article::BoardName;
+
     use super::*;
 
+    #[test]
+    fn test_compose_search_url() {
+        let query = SearchQuery::new().keyword("問卦").recommend(10);
+
+        assert_eq!(
+            compose_search_url(&BoardName::Gossiping.into(), &query, 2),
+            "https://www.ptt.cc/bbs/Gossiping/search?page=2&q=%E5%95%8F%E5%8D%A6+recommend%3A10"
+        );
+    }
+
+    #[test]
+    fn test_merge_article_urls() {
+        let merged = merge_article_urls(vec![
+            vec![
+                "https://www.ptt.cc/bbs/Gossiping/M.1597463395.A.478.html".to_owned(),
+                "https://www.ptt.cc/bbs/Gossiping/M.1597000000.A.001.html".to_owned(),
+            ],
+            vec![
+                "https://www.ptt.cc/bbs/Stock/M.1597463395.A.478.html".to_owned(),
+                "https://www.ptt.cc/bbs/Stock/M.1597200000.A.002.html".to_owned(),
+            ],
+        ]);
+
+        assert_eq!(
+            merged,
+            vec![
+                "https://www.ptt.cc/bbs/Gossiping/M.1597000000.A.001.html",
+                "https://www.ptt.cc/bbs/Stock/M.1597200000.A.002.html",
+                "https://www.ptt.cc/bbs/Gossiping/M.1597463395.A.478.html",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_supported_url() {
+        assert!(is_supported_url(
+            "https://www.ptt.cc/bbs/Gossiping/M.1597463395.A.478.html"
+        ));
+        assert!(is_supported_url(
+            "https://www.ptt.cc/man/Gossiping/D8D5/M.1123769450.A.A1A.html"
+        ));
+        assert!(!is_supported_url(
+            "https://www.ptt.cc/cls/Gossiping/M.1597463395.A.478.html"
+        ));
+    }
+
+    #[test]
+    fn test_unseen_feed_urls() {
+        let entry = |id: &str| FeedEntry {
+            id: id.to_owned(),
+            title: "".to_owned(),
+            author_id: "".to_owned(),
+            published: None,
+            updated: None,
+            url: format!("https://www.ptt.cc/bbs/Gossiping/{}.html", id),
+        };
+        let entries = vec![
+            entry("M.1597463400.A.001"),
+            entry("M.1597463395.A.478"),
+            entry("M.1597463300.A.002"),
+        ];
+        let seen_ids = vec!["M.1597463395.A.478".to_owned()].into_iter().collect();
+
+        assert_eq!(
+            unseen_feed_urls(entries, &seen_ids),
+            vec![
+                "https://www.ptt.cc/bbs/Gossiping/M.1597463300.A.002.html",
+                "https://www.ptt.cc/bbs/Gossiping/M.1597463400.A.001.html",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_last_seen() {
+        assert_eq!(
+            "M.1597463395.A.478".parse::<LastSeen>().unwrap(),
+            LastSeen::Id("M.1597463395.A.478".to_owned())
+        );
+        assert_eq!(
+            "1597463395".parse::<LastSeen>().unwrap(),
+            LastSeen::Timestamp(1_597_463_395)
+        );
+        assert!("latest".parse::<LastSeen>().is_err());
+    }
+
+    #[test]
+    fn test_last_seen_is_older_than() {
+        let last_seen = LastSeen::Id("M.1597463395.A.478".to_owned());
+        assert!(last_seen.is_older_than("M.1597463396.A.001"));
+        assert!(last_seen.is_older_than("M.1597463395.A.479"));
+        assert!(last_seen.is_older_than("M.1597463395.A.001"));
+        assert!(!last_seen.is_older_than("M.1597463395.A.478"));
+        assert!(!last_seen.is_older_than("M.1597463394.A.FFF"));
+
+        let last_seen = LastSeen::Timestamp(1_597_463_395);
+        assert!(last_seen.is_older_than("M.1597463396.A.001"));
+        assert!(!last_seen.is_older_than("M.1597463395.A.478"));
+    }
+
     #[tokio::test]
     async fn test_crawl_not_ptt_url() {
         let client = create_client(None, None, None).await.unwrap();
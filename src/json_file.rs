@@ -0,0 +1,36 @@
+use std::fs::{self, File};
+use std::io::{self, prelude::*, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Load a value from the JSON file at given path.
+pub(crate) fn load_json<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> io::Result<T> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+/// Save the value as JSON file at given path.
+/// The file is replaced only after the whole value is written.
+pub(crate) fn save_json<T: Serialize, P: AsRef<Path>>(value: &T, path: P) -> io::Result<()> {
+    replace_file(path, |writer| Ok(serde_json::to_writer(writer, value)?))
+}
+
+/// Replace the file at given path with what is written by `write`.
+/// It is written to a temporary file first, which is renamed to given path
+/// only after all of them are written.
+pub fn replace_file<P, F>(path: P, write: F) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+    let path = path.as_ref();
+    let mut temp_path = PathBuf::from(path);
+    temp_path.set_extension("tmp");
+    {
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        write(&mut writer)?;
+        writer.flush()?;
+    }
+    fs::rename(&temp_path, path)
+}
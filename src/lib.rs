@@ -8,6 +8,7 @@ extern crate lazy_static;
 extern crate load_file;
 #[macro_use]
 extern crate log;
+extern crate rand;
 extern crate regex;
 extern crate reqwest;
 extern crate select;
@@ -21,5 +22,12 @@ extern crate tokio;
 extern crate url;
 
 pub mod article;
+pub mod checkpoint;
 pub mod crawler;
+pub mod json_file;
 mod parser;
+pub mod rate_limiter;
+pub mod retry;
+pub mod search;
+pub mod thread;
+pub mod version;
@@ -3,21 +3,35 @@ extern crate log;
 extern crate pretty_env_logger;
 extern crate ptt_crawler;
 
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::fs::File;
-use std::io::prelude::*;
+use std::fs::{File, OpenOptions};
+use std::io::{self, prelude::*, BufReader};
 use std::ops::RangeInclusive;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::str::FromStr;
 use std::time::Duration;
 
+use chrono::prelude::*;
 use enum_iterator::IntoEnumIterator;
 use fake_useragent::UserAgents;
-use reqwest::{Client, Proxy};
+use futures::StreamExt;
+use reqwest::Proxy;
+use serde::Serialize;
 use structopt::StructOpt;
 
-use ptt_crawler::article::BoardName;
-use ptt_crawler::crawler;
+use ptt_crawler::article::{self, Article, Board, BoardCategory, BoardInfo, BoardName};
+use ptt_crawler::checkpoint::{Checkpoint, SeenState};
+use ptt_crawler::crawler::{self, Client, LastSeen};
+use ptt_crawler::json_file;
+use ptt_crawler::rate_limiter::RateLimiter;
+use ptt_crawler::retry::RetryPolicy;
+use ptt_crawler::search::SearchQuery;
+use ptt_crawler::version::VersionStore;
+
+/// Maximum number of article IDs kept for each board in seen state
+const MAX_SEEN_IDS: usize = 1000;
 
 #[derive(StructOpt)]
 #[structopt(
@@ -33,6 +47,9 @@ struct Opt {
     /// Outputs results to file in JSON format
     #[structopt(short, long, parse(from_os_str))]
     output: Option<PathBuf>,
+    /// Format of results. "jsonl" writes one compact JSON per line as soon as it is crawled
+    #[structopt(short, long, default_value = "json", possible_values = &["json", "jsonl"])]
+    format: OutputFormat,
     /// User agent that crawler should use. Pass "random" to use randomly generated one.
     #[structopt(short, long, parse(from_os_str))]
     user_agent: Option<PathBuf>,
@@ -42,24 +59,311 @@ struct Opt {
     /// Timeout in ms for the connect phase of a request
     #[structopt(short, long, default_value = "3000")]
     timeout: u64,
+    /// Maximum number of requests per second sent to PTT. If option is absent, requests are not limited.
+    #[structopt(long)]
+    rate_limit: Option<f64>,
+    /// Maximum number of requests sent at once when rate limited
+    #[structopt(long, default_value = "1")]
+    burst: u32,
+    /// Maximum random delay in ms added to every request when rate limited
+    #[structopt(long, default_value = "0")]
+    jitter: u64,
+    /// Maximum number of attempts of a request before giving up
+    #[structopt(long, default_value = "3")]
+    max_attempts: u32,
+    /// Delay in ms before the first retry, doubled after every failed retry
+    #[structopt(long, default_value = "500")]
+    backoff: u64,
+    /// Maximum delay in ms between two attempts
+    #[structopt(long, default_value = "30000")]
+    max_backoff: u64,
 
     #[structopt(subcommand)]
     cmd: SubCommand,
 }
 
+enum OutputFormat {
+    Json,
+    JsonLines,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::JsonLines),
+            _ => Err(format!("Unknown format \"{}\"", s)),
+        }
+    }
+}
+
+/// Output writes results to either a file or stdout in given format.
+struct Output {
+    writer: Box<dyn Write>,
+    format: OutputFormat,
+    count: usize,
+}
+
+impl Output {
+    /// Results are appended to the file if `append` is true.
+    fn new(path: Option<PathBuf>, format: OutputFormat, append: bool) -> Output {
+        let writer: Box<dyn Write> = match path {
+            Some(path) => {
+                let file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .append(append)
+                    .truncate(!append)
+                    .open(&path);
+                let file = file.unwrap_or_else(|_| {
+                    let alt_output = env::current_dir()
+                        .unwrap()
+                        .join("result.json")
+                        .into_os_string()
+                        .into_string()
+                        .unwrap();
+                    eprintln!(
+                        "Error: Failed to create file at {}, change to {}",
+                        path.into_os_string().into_string().unwrap(),
+                        alt_output
+                    );
+                    File::create(alt_output).unwrap()
+                });
+                Box::new(file)
+            }
+            None => {
+                if let OutputFormat::Json = format {
+                    println!("Results in JSON format:");
+                }
+                Box::new(io::stdout())
+            }
+        };
+        Output {
+            writer,
+            format,
+            count: 0,
+        }
+    }
+
+    /// Start a list of results. Has no effect in JSON Lines format.
+    fn start_list(&mut self) {
+        if let OutputFormat::Json = self.format {
+            self.write_str("[");
+        }
+    }
+
+    /// Write a result, either a single one or an element of list.
+    fn write<T: Serialize>(&mut self, result: &T) {
+        match self.format {
+            OutputFormat::Json => {
+                if self.count > 0 {
+                    self.write_str(",");
+                }
+                self.write_str(&serde_json::to_string_pretty(result).unwrap());
+            }
+            OutputFormat::JsonLines => {
+                self.write_str(&serde_json::to_string(result).unwrap());
+                self.write_str("\n");
+            }
+        }
+        self.count += 1;
+    }
+
+    /// End a list of results. Has no effect in JSON Lines format.
+    fn end_list(&mut self) {
+        if let OutputFormat::Json = self.format {
+            self.write_str("]\n");
+        }
+    }
+
+    fn write_str(&mut self, results: &str) {
+        self.writer
+            .write_all(results.as_bytes())
+            .and_then(|_| self.writer.flush())
+            .unwrap_or_else(|e| {
+                eprintln!("Error: Failed to write results with error\n{:#?}", e);
+                process::exit(1)
+            });
+    }
+}
+
 #[derive(StructOpt)]
 enum SubCommand {
     /// Crawls given board with page range
     Board {
-        /// Lists available boards
+        /// Lists known boards
         #[structopt(short = "l", long = "list")]
         show_list: bool,
+        /// Board name
+        #[structopt(
+            name = "Board",
+            parse(from_os_str),
+            required_unless = "resume",
+            conflicts_with = "resume"
+        )]
+        board: Option<PathBuf>,
+        /// Range of page index. If option is absent, all pages will be processed.
+        #[structopt(short, long, max_values(2))]
+        range: Option<Vec<u32>>,
+        /// Maximum number of pages or articles fetched at the same time
+        #[structopt(short, long, default_value = "1")]
+        concurrency: usize,
+        /// Saves progress to given checkpoint file after every article. Requires "-f jsonl".
+        #[structopt(long, parse(from_os_str))]
+        checkpoint: Option<PathBuf>,
+        /// Resumes crawling from given checkpoint file. Results are appended to the output file. Requires "-f jsonl".
+        #[structopt(long, parse(from_os_str))]
+        resume: Option<PathBuf>,
+        /// Crawls only articles newer than given article ID (e.g. M.1597463395.A.478) or Unix timestamp
+        #[structopt(long, conflicts_with_all = &["range", "checkpoint", "resume"])]
+        newer_than: Option<LastSeen>,
+        /// Crawls only articles posted on or after given date (e.g. 2020-03-01)
+        #[structopt(long, parse(try_from_str = parse_date), conflicts_with_all = &["range", "checkpoint", "resume", "newer-than"])]
+        since: Option<DateTime<FixedOffset>>,
+        /// Crawls only articles posted on or before given date (e.g. 2020-03-31)
+        #[structopt(long, parse(try_from_str = parse_date), conflicts_with_all = &["range", "checkpoint", "resume", "newer-than"])]
+        until: Option<DateTime<FixedOffset>>,
+    },
+    /// Crawls listing entries of given board with page range, without crawling articles
+    Listing {
         /// Board name
         #[structopt(name = "Board", parse(from_os_str))]
         board: PathBuf,
         /// Range of page index. If option is absent, all pages will be processed.
         #[structopt(short, long, max_values(2))]
         range: Option<Vec<u32>>,
+        /// Maximum number of pages fetched at the same time
+        #[structopt(short, long, default_value = "1")]
+        concurrency: usize,
+        /// Includes entries of deleted articles
+        #[structopt(long)]
+        include_deleted: bool,
+    },
+    /// Searches articles in given board
+    Search {
+        /// Board name
+        #[structopt(name = "Board", parse(from_os_str))]
+        board: PathBuf,
+        /// Keywords in title of articles
+        #[structopt(name = "Query")]
+        keywords: Vec<String>,
+        /// Searches articles posted by given author ID
+        #[structopt(long)]
+        author: Option<String>,
+        /// Searches articles in the same thread of given title
+        #[structopt(long)]
+        thread: Option<String>,
+        /// Searches articles with at least given push count, or at most if negative
+        #[structopt(long, allow_hyphen_values = true)]
+        recommend: Option<i32>,
+        /// Maximum number of pages of search results. If option is absent, all pages will be processed.
+        #[structopt(long)]
+        pages: Option<u32>,
+        /// Crawls articles of search results instead of listing entries
+        #[structopt(long)]
+        articles: bool,
+        /// Maximum number of articles fetched at the same time
+        #[structopt(short, long, default_value = "1")]
+        concurrency: usize,
+    },
+    /// Crawls articles in the same thread of given title, sorted by date
+    Thread {
+        /// Board name
+        #[structopt(name = "Board", parse(from_os_str))]
+        board: PathBuf,
+        /// Title of any article in the thread, e.g. "Re: [問卦] 有沒有八卦"
+        #[structopt(name = "Title")]
+        title: String,
+        /// Maximum number of pages of search results. If option is absent, all pages will be processed.
+        #[structopt(long)]
+        pages: Option<u32>,
+        /// Maximum number of articles fetched at the same time
+        #[structopt(short, long, default_value = "1")]
+        concurrency: usize,
+    },
+    /// Crawls articles posted by given author in given boards
+    Author {
+        /// Author ID
+        #[structopt(name = "ID")]
+        author_id: String,
+        /// Boards to search articles in
+        #[structopt(long, parse(from_os_str), required = true, min_values = 1)]
+        boards: Vec<PathBuf>,
+        /// Maximum number of pages of search results in each board. If option is absent, all pages will be processed.
+        #[structopt(long)]
+        pages: Option<u32>,
+        /// Maximum number of articles fetched at the same time
+        #[structopt(short, long, default_value = "1")]
+        concurrency: usize,
+    },
+    /// Crawls directories and articles in the digest (精華區) of given board
+    Man {
+        /// Board name
+        #[structopt(name = "Board", parse(from_os_str))]
+        board: PathBuf,
+        /// Maximum depth of directories to crawl. Directories in the root of digest have depth 1.
+        #[structopt(long, default_value = "10")]
+        depth: u32,
+    },
+    /// Crawls boards listed in hot boards or the classification tree. Moderators are not available on the web version of PTT.
+    Boards {
+        /// Crawls hot boards only
+        #[structopt(long)]
+        hot: bool,
+        /// Maximum depth of categories to crawl in the classification tree
+        #[structopt(long, default_value = "10")]
+        depth: u32,
+        /// Prints boards as indented text instead of JSON
+        #[structopt(long)]
+        plain: bool,
+    },
+    /// Polls given boards continuously and crawls new articles until interrupted
+    Watch {
+        /// Board names
+        #[structopt(name = "Board", parse(from_os_str), required = true, min_values = 1)]
+        boards: Vec<PathBuf>,
+        /// Interval in seconds between two polls
+        #[structopt(short, long, default_value = "60")]
+        interval: u64,
+        /// Saves IDs of crawled articles to given file, so that they are skipped after restart.
+        /// Results are appended to the output file if the state file exists, which requires "-f jsonl".
+        #[structopt(long, parse(from_os_str))]
+        state: Option<PathBuf>,
+        /// Skips articles existing when watching starts
+        #[structopt(long)]
+        skip_existing: bool,
+        /// Maximum number of articles fetched at the same time
+        #[structopt(short, long, default_value = "1")]
+        concurrency: usize,
+    },
+    /// Crawls again articles in given result file, and outputs replies appended since then
+    Refresh {
+        /// File of crawled articles, in either JSON or JSON Lines format
+        #[structopt(name = "File", parse(from_os_str))]
+        file: PathBuf,
+        /// Updates replies of articles in the file, so that they are not output again next time
+        #[structopt(long)]
+        update: bool,
+        /// Crawls only articles posted within given number of hours
+        #[structopt(long, default_value = "72")]
+        max_age: i64,
+        /// Maximum number of articles fetched at the same time
+        #[structopt(short, long, default_value = "1")]
+        concurrency: usize,
+    },
+    /// Crawls given articles again, records their versions and outputs changes of edited ones
+    Track {
+        /// File storing versions of tracked articles. Created if not exist.
+        #[structopt(name = "History", parse(from_os_str))]
+        history: PathBuf,
+        /// URLs of articles to track. If absent, all tracked articles will be crawled.
+        #[structopt(name = "URL")]
+        urls: Vec<String>,
+        /// Maximum number of articles fetched at the same time
+        #[structopt(short, long, default_value = "1")]
+        concurrency: usize,
     },
     /// Crawls given URL of article directly
     Url {
@@ -99,42 +403,48 @@ async fn main() {
         proxies = Some(vec![proxy])
     }
 
-    let json_output: String;
+    let mut rate_limiter: Option<RateLimiter> = None;
+    if let Some(rate_limit) = opt.rate_limit {
+        if rate_limit <= 0.0 {
+            eprintln!("Error: Rate limit should be greater than 0");
+            process::exit(1);
+        }
+        rate_limiter =
+            Some(RateLimiter::new(rate_limit, opt.burst).jitter(Duration::from_millis(opt.jitter)));
+    }
+
+    let retry_policy = RetryPolicy {
+        max_attempts: opt.max_attempts.max(1),
+        base_delay: Duration::from_millis(opt.backoff),
+        max_delay: Duration::from_millis(opt.max_backoff),
+        ..RetryPolicy::default()
+    };
+
     match opt.cmd {
         SubCommand::Url { url } => {
             let url_string = url.into_os_string().into_string().unwrap();
 
-            println!("Start crawling URL \"{}\"", url_string);
-            let client = create_client(user_agent, proxies, opt.timeout).await;
-            json_output = match crawler::crawl_url(&client, &url_string, None).await {
-                Ok(article) => serde_json::to_string_pretty(&article).unwrap(),
-                Err(e) => {
+            eprintln!("Start crawling URL \"{}\"", url_string);
+            let client =
+                create_client(user_agent, proxies, opt.timeout, rate_limiter, retry_policy).await;
+            let article = crawler::crawl_url(&client, &url_string, None)
+                .await
+                .unwrap_or_else(|e| {
                     eprintln!("Error: Failed to crawl with error\n{:#?}", e);
                     process::exit(1)
-                }
-            };
+                });
+            let mut output = Output::new(opt.output, opt.format, false);
+            output.write(&article);
         }
-        SubCommand::Board {
-            show_list,
+        SubCommand::Listing {
             board,
             range,
+            concurrency,
+            include_deleted,
         } => {
-            if show_list {
-                for board in BoardName::into_enum_iter() {
-                    println!("{}", board.to_string());
-                }
-                process::exit(0);
-            }
-
-            let board_string = board.into_os_string().into_string().unwrap();
-            let board = board_string.parse::<BoardName>().unwrap_or_else(|_| {
-                eprintln!(
-                    "Error: Invalid board name \"{}\". Use --list to see available options",
-                    board_string
-                );
-                process::exit(1);
-            });
-            let client = create_client(user_agent, proxies, opt.timeout).await;
+            let board = parse_board(board);
+            let client =
+                create_client(user_agent, proxies, opt.timeout, rate_limiter, retry_policy).await;
             let page_count = crawler::crawl_page_count(&client, &board)
                 .await
                 .unwrap_or(0);
@@ -148,50 +458,801 @@ async fn main() {
                     process::exit(1);
                 });
 
-            println!(
-                "Start crawling board \"{}\" from page {} to {}",
+            eprintln!(
+                "Start crawling listing of board \"{}\" from page {} to {}",
                 board,
                 range.start(),
                 range.end()
             );
-            json_output = match crawler::crawl_page_articles(&client, &board, &range).await {
-                Ok(articles) => serde_json::to_string_pretty(&articles).unwrap(),
-                Err(e) => {
+            let entries = crawler::crawl_page_listing(&client, &board, &range, concurrency)
+                .await
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: Failed to crawl with error\n{:#?}", e);
+                    process::exit(1);
+                });
+            let mut output = Output::new(opt.output, opt.format, false);
+            output.start_list();
+            for entry in entries
+                .iter()
+                .filter(|e| include_deleted || e.deletion.is_none())
+            {
+                output.write(entry);
+            }
+            output.end_list();
+        }
+        SubCommand::Search {
+            board,
+            keywords,
+            author,
+            thread,
+            recommend,
+            pages,
+            articles,
+            concurrency,
+        } => {
+            let board = parse_board(board);
+            let mut query = keywords
+                .into_iter()
+                .fold(SearchQuery::new(), |q, k| q.keyword(k));
+            if let Some(author) = author {
+                query = query.author(author);
+            }
+            if let Some(thread) = thread {
+                query = query.thread(thread);
+            }
+            if let Some(recommend) = recommend {
+                query = query.recommend(recommend);
+            }
+            if query.is_empty() {
+                eprintln!("Error: Query should not be empty");
+                process::exit(1);
+            }
+
+            eprintln!("Start searching \"{}\" in board \"{}\"", query, board);
+            let client =
+                create_client(user_agent, proxies, opt.timeout, rate_limiter, retry_policy).await;
+            let entries = crawler::crawl_search(&client, &board, &query, pages)
+                .await
+                .unwrap_or_else(|e| {
                     eprintln!("Error: Failed to crawl with error\n{:#?}", e);
                     process::exit(1);
+                });
+            let mut output = Output::new(opt.output, opt.format, false);
+            output.start_list();
+            if articles {
+                let urls = entries.into_iter().flat_map(|e| e.url).collect();
+                let mut articles =
+                    crawler::crawl_urls_stream(&client, urls, concurrency).boxed_local();
+                while let Some((url, result)) = articles.next().await {
+                    match result {
+                        Ok(article) => output.write(&article),
+                        Err(e) => eprintln!("Error: Failed to crawl {} with error\n{:#?}", url, e),
+                    }
                 }
-            };
+            } else {
+                for entry in entries.iter() {
+                    output.write(entry);
+                }
+            }
+            output.end_list();
         }
-    }
+        SubCommand::Thread {
+            board,
+            title,
+            pages,
+            concurrency,
+        } => {
+            let board = parse_board(board);
+            eprintln!("Start crawling thread \"{}\" in board \"{}\"", title, board);
+            let client =
+                create_client(user_agent, proxies, opt.timeout, rate_limiter, retry_policy).await;
+            let thread = crawler::crawl_thread(&client, &board, &title, pages, concurrency)
+                .await
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: Failed to crawl with error\n{:#?}", e);
+                    process::exit(1);
+                });
+            Output::new(opt.output, opt.format, false).write(&thread);
+        }
+        SubCommand::Author {
+            author_id,
+            boards,
+            pages,
+            concurrency,
+        } => {
+            let boards: Vec<Board> = boards.into_iter().map(parse_board).collect();
+            eprintln!(
+                "Start crawling articles of author \"{}\" in {} boards",
+                author_id,
+                boards.len()
+            );
+            let client =
+                create_client(user_agent, proxies, opt.timeout, rate_limiter, retry_policy).await;
+            let articles = crawler::crawl_author(&client, &author_id, &boards, pages, concurrency)
+                .await
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: Failed to crawl with error\n{:#?}", e);
+                    process::exit(1);
+                });
+            let mut output = Output::new(opt.output, opt.format, false);
+            output.start_list();
+            for article in articles.iter() {
+                output.write(article);
+            }
+            output.end_list();
+        }
+        SubCommand::Watch {
+            boards,
+            interval,
+            state,
+            skip_existing,
+            concurrency,
+        } => {
+            let boards: Vec<Board> = boards.into_iter().map(parse_board).collect();
+            // a JSON array cannot be appended after restart
+            if state.is_some() && opt.output.is_some() {
+                if let OutputFormat::Json = opt.format {
+                    eprintln!("Error: Appending results after restart requires JSON Lines format (-f jsonl)");
+                    process::exit(1);
+                }
+            }
+            let state_exists = matches!(&state, Some(path) if path.exists());
+            let mut seen = match &state {
+                Some(path) if state_exists => SeenState::load(path).unwrap_or_else(|e| {
+                    eprintln!(
+                        "Error: Failed to load seen state {}\n{:#?}",
+                        path.display(),
+                        e
+                    );
+                    process::exit(1);
+                }),
+                _ => SeenState::new(),
+            };
+            let client =
+                create_client(user_agent, proxies, opt.timeout, rate_limiter, retry_policy).await;
+            if skip_existing {
+                for board in boards.iter() {
+                    let seen_ids: HashSet<String> = seen.ids(board).into_iter().collect();
+                    match crawler::crawl_unseen_urls(&client, board, &seen_ids).await {
+                        Ok(urls) => urls
+                            .iter()
+                            .flat_map(|u| article::id_from_url(u))
+                            .for_each(|id| seen.insert(board, id)),
+                        Err(e) => eprintln!("Error: Failed to crawl with error\n{:#?}", e),
+                    }
+                }
+            }
 
-    if let Some(output) = opt.output {
-        let mut file = File::create(&output).unwrap_or_else(|_| {
-            let alt_output = env::current_dir()
-                .unwrap()
-                .join("result.json")
-                .into_os_string()
-                .into_string()
-                .unwrap();
             eprintln!(
-                "Error: Failed to create file at {}, change to {}",
-                output.into_os_string().into_string().unwrap(),
-                alt_output
+                "Start watching {} boards every {} seconds. Press Ctrl-C to stop",
+                boards.len(),
+                interval
             );
-            File::create(alt_output).unwrap()
-        });
-        file.write_all(json_output.as_bytes()).unwrap_or_else(|e| {
-            eprintln!("Error: Failed to write results with error\n{:#?}", e);
+            let mut output = Output::new(opt.output, opt.format, state_exists);
+            output.start_list();
+            let shutdown = shutdown_signal();
+            tokio::pin!(shutdown);
+            loop {
+                tokio::select! {
+                    _ = &mut shutdown => break,
+                    _ = watch_boards(&client, &boards, &mut seen, concurrency, &mut output) => {}
+                }
+                if let Some(path) = &state {
+                    save_seen_state(&seen, path);
+                }
+                tokio::select! {
+                    _ = &mut shutdown => break,
+                    _ = tokio::time::delay_for(Duration::from_secs(interval)) => {}
+                }
+            }
+            output.end_list();
+            if let Some(path) = &state {
+                save_seen_state(&seen, path);
+            }
+            eprintln!("Stop watching boards");
+        }
+        SubCommand::Refresh {
+            file,
+            update,
+            max_age,
+            concurrency,
+        } => {
+            let (articles, file_format) = load_articles(&file).unwrap_or_else(|e| {
+                eprintln!(
+                    "Error: Failed to load articles from {}\n{:#?}",
+                    file.display(),
+                    e
+                );
+                process::exit(1);
+            });
+            eprintln!(
+                "Start refreshing articles posted within {} hours among {} articles",
+                max_age,
+                articles.len()
+            );
+            let client =
+                create_client(user_agent, proxies, opt.timeout, rate_limiter, retry_policy).await;
+            let mut refreshed_articles = articles.clone();
+            let indices: HashMap<(Board, String), usize> = articles
+                .iter()
+                .enumerate()
+                .map(|(i, a)| ((a.meta.board.clone(), a.meta.id.clone()), i))
+                .collect();
+            let mut output = Output::new(opt.output, opt.format, false);
+            output.start_list();
+            let mut updates = crawler::crawl_reply_updates_stream(
+                &client,
+                articles,
+                chrono::Duration::hours(max_age),
+                concurrency,
+            )
+            .boxed_local();
+            while let Some(result) = updates.next().await {
+                match result {
+                    Ok(update) => {
+                        output.write(&update);
+                        if let Some(&i) = indices.get(&(update.board.clone(), update.id.clone())) {
+                            update.apply(&mut refreshed_articles[i]);
+                        }
+                    }
+                    Err(e) => eprintln!("Error: Failed to crawl with error\n{:#?}", e),
+                }
+            }
+            output.end_list();
+            if update && output.count > 0 {
+                save_articles(&file, &refreshed_articles, &file_format).unwrap_or_else(|e| {
+                    eprintln!(
+                        "Error: Failed to save articles to {}\n{:#?}",
+                        file.display(),
+                        e
+                    );
+                    process::exit(1);
+                });
+            }
+        }
+        SubCommand::Track {
+            history,
+            urls,
+            concurrency,
+        } => {
+            let mut store = if history.exists() {
+                VersionStore::load(&history).unwrap_or_else(|e| {
+                    eprintln!(
+                        "Error: Failed to load history {}\n{:#?}",
+                        history.display(),
+                        e
+                    );
+                    process::exit(1);
+                })
+            } else {
+                VersionStore::new()
+            };
+            let urls = if urls.is_empty() {
+                store
+                    .iter()
+                    .map(|h| crawler::compose_article_url(&h.board, &h.id))
+                    .collect()
+            } else {
+                urls
+            };
+
+            eprintln!("Start tracking {} articles", urls.len());
+            let client =
+                create_client(user_agent, proxies, opt.timeout, rate_limiter, retry_policy).await;
+            let mut output = Output::new(opt.output, opt.format, false);
+            output.start_list();
+            let mut articles = crawler::crawl_urls_stream(&client, urls, concurrency).boxed_local();
+            while let Some((url, result)) = articles.next().await {
+                let article = match result {
+                    Ok(article) => article,
+                    Err(e) => {
+                        eprintln!("Error: Failed to crawl {} with error\n{:#?}", url, e);
+                        continue;
+                    }
+                };
+                let crawled_at = Utc::now().with_timezone(&FixedOffset::east(0));
+                if let Some(change) = store.record(article, crawled_at) {
+                    output.write(&change);
+                }
+            }
+            output.end_list();
+            store.save(&history).unwrap_or_else(|e| {
+                eprintln!(
+                    "Error: Failed to save history {}\n{:#?}",
+                    history.display(),
+                    e
+                );
+                process::exit(1);
+            });
+        }
+        SubCommand::Man { board, depth } => {
+            let board = parse_board(board);
+            eprintln!("Start crawling digest of board \"{}\"", board);
+            let client =
+                create_client(user_agent, proxies, opt.timeout, rate_limiter, retry_policy).await;
+            let mut output = Output::new(opt.output, opt.format, false);
+            output.start_list();
+            let mut nodes = crawler::crawl_man_stream(&client, &board, depth).boxed_local();
+            while let Some(result) = nodes.next().await {
+                match result {
+                    Ok(node) => output.write(&node),
+                    Err(e) => eprintln!("Error: Failed to crawl with error\n{:#?}", e),
+                }
+            }
+            output.end_list();
+        }
+        SubCommand::Boards { hot, depth, plain } => {
+            let client =
+                create_client(user_agent, proxies, opt.timeout, rate_limiter, retry_policy).await;
+            if hot {
+                eprintln!("Start crawling hot boards");
+                let boards = crawler::crawl_hot_boards(&client)
+                    .await
+                    .unwrap_or_else(|e| {
+                        eprintln!("Error: Failed to crawl with error\n{:#?}", e);
+                        process::exit(1);
+                    });
+                if plain {
+                    for board in boards.iter() {
+                        print_board(board, 0);
+                    }
+                } else {
+                    let mut output = Output::new(opt.output, opt.format, false);
+                    output.start_list();
+                    for board in boards.iter() {
+                        output.write(board);
+                    }
+                    output.end_list();
+                }
+            } else {
+                eprintln!("Start crawling classification tree of boards");
+                let tree = crawler::crawl_board_tree(&client, depth)
+                    .await
+                    .unwrap_or_else(|e| {
+                        eprintln!("Error: Failed to crawl with error\n{:#?}", e);
+                        process::exit(1);
+                    });
+                if plain {
+                    print_category(&tree, 0);
+                } else {
+                    Output::new(opt.output, opt.format, false).write(&tree);
+                }
+            }
+        }
+        SubCommand::Board {
+            show_list,
+            board,
+            range,
+            concurrency,
+            checkpoint,
+            resume,
+            newer_than,
+            since,
+            until,
+        } => {
+            if show_list {
+                for board in BoardName::into_enum_iter() {
+                    println!("{}", board);
+                }
+                process::exit(0);
+            }
+            // a JSON array cannot be appended after being interrupted
+            if checkpoint.is_some() || resume.is_some() {
+                if let OutputFormat::Json = opt.format {
+                    eprintln!("Error: Checkpoint and resume require JSON Lines format (-f jsonl)");
+                    process::exit(1);
+                }
+            }
+
+            let client =
+                create_client(user_agent, proxies, opt.timeout, rate_limiter, retry_policy).await;
+            let mut output;
+            if let Some(resume) = resume {
+                let mut saved_checkpoint = Checkpoint::load(&resume).unwrap_or_else(|e| {
+                    eprintln!(
+                        "Error: Failed to load checkpoint {}\n{:#?}",
+                        resume.display(),
+                        e
+                    );
+                    process::exit(1);
+                });
+                eprintln!(
+                    "Resume crawling board \"{}\" from page {} to {}",
+                    saved_checkpoint.board, saved_checkpoint.first_page, saved_checkpoint.last_page
+                );
+                output = Output::new(opt.output, opt.format, true);
+                let checkpoint_path = checkpoint.unwrap_or(resume);
+                output.start_list();
+                crawl_with_checkpoint(
+                    &client,
+                    &mut saved_checkpoint,
+                    &checkpoint_path,
+                    concurrency,
+                    &mut output,
+                )
+                .await;
+                output.end_list();
+            } else if since.is_some() || until.is_some() {
+                let board = parse_board(board.unwrap());
+                let since = since.unwrap_or_else(|| {
+                    FixedOffset::east(0).from_utc_datetime(&NaiveDateTime::from_timestamp(0, 0))
+                });
+                // until the end of given date
+                let until = until
+                    .map(|d| d + chrono::Duration::days(1))
+                    .unwrap_or_else(|| Utc::now().with_timezone(&FixedOffset::east(0)));
+                eprintln!(
+                    "Start crawling board \"{}\" from {} to {}",
+                    board, since, until
+                );
+                let mut articles = crawler::crawl_date_range_articles_stream(
+                    &client,
+                    &board,
+                    since,
+                    until,
+                    concurrency,
+                )
+                .await
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: Failed to crawl with error\n{:#?}", e);
+                    process::exit(1);
+                })
+                .boxed_local();
+                output = Output::new(opt.output, opt.format, false);
+                output.start_list();
+                while let Some(result) = articles.next().await {
+                    match result {
+                        Ok(article) => output.write(&article),
+                        Err(e) => eprintln!("Error: Failed to crawl with error\n{:#?}", e),
+                    }
+                }
+                output.end_list();
+            } else if let Some(last_seen) = newer_than {
+                let board = parse_board(board.unwrap());
+                eprintln!(
+                    "Start crawling board \"{}\" for articles newer than {:?}",
+                    board, last_seen
+                );
+                let urls = crawler::crawl_new_urls(&client, &board, &last_seen)
+                    .await
+                    .unwrap_or_else(|e| {
+                        eprintln!("Error: Failed to crawl with error\n{:#?}", e);
+                        process::exit(1);
+                    });
+                if urls.is_empty() {
+                    eprintln!("No new article was found");
+                    return;
+                }
+                output = Output::new(opt.output, opt.format, false);
+                output.start_list();
+                // newest article crawled without any failure before it, so
+                // that articles failed to crawl are crawled again next time
+                let mut newest_id = None;
+                let mut failed = false;
+                let mut articles =
+                    crawler::crawl_urls_stream(&client, urls, concurrency).boxed_local();
+                while let Some((url, result)) = articles.next().await {
+                    match result {
+                        Ok(article) => {
+                            output.write(&article);
+                            if !failed {
+                                newest_id = Some(article.meta.id);
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("Error: Failed to crawl {} with error\n{:#?}", url, e);
+                            failed = true;
+                        }
+                    }
+                }
+                output.end_list();
+                if let Some(id) = newest_id {
+                    eprintln!("Newest article: {}", id);
+                }
+            } else {
+                let board = parse_board(board.unwrap());
+                let page_count = crawler::crawl_page_count(&client, &board)
+                    .await
+                    .unwrap_or(0);
+                let range = adjust_board_range(page_count, range)
+                    .await
+                    .unwrap_or_else(|_| {
+                        eprintln!(
+                            "Error: Invalid page range. Should between 1 and {}",
+                            page_count
+                        );
+                        process::exit(1);
+                    });
+
+                eprintln!(
+                    "Start crawling board \"{}\" from page {} to {}",
+                    board,
+                    range.start(),
+                    range.end()
+                );
+                output = Output::new(opt.output, opt.format, false);
+                output.start_list();
+                if let Some(checkpoint_path) = checkpoint {
+                    let mut new_checkpoint = Checkpoint::new(board, &range);
+                    crawl_with_checkpoint(
+                        &client,
+                        &mut new_checkpoint,
+                        &checkpoint_path,
+                        concurrency,
+                        &mut output,
+                    )
+                    .await;
+                } else {
+                    let mut articles =
+                        crawler::crawl_page_articles_stream(&client, &board, &range, concurrency)
+                            .boxed_local();
+                    while let Some(result) = articles.next().await {
+                        match result {
+                            Ok(article) => output.write(&article),
+                            Err(e) => eprintln!("Error: Failed to crawl with error\n{:#?}", e),
+                        }
+                    }
+                }
+                output.end_list();
+            }
+            if output.count == 0 {
+                eprintln!("Error: No article was crawled");
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Crawl the remaining pages of checkpoint page by page, and save the
+/// checkpoint after every article and page. URLs failed in previous run are
+/// crawled first.
+async fn crawl_with_checkpoint(
+    client: &Client,
+    checkpoint: &mut Checkpoint,
+    checkpoint_path: &Path,
+    concurrency: usize,
+    output: &mut Output,
+) {
+    let failed_urls: Vec<String> = checkpoint.failed_urls.iter().cloned().collect();
+    let mut article_urls = vec![];
+    for url in failed_urls {
+        if !url.contains("/index") {
+            article_urls.push(url);
+            continue;
+        }
+        // URL of index page
+        match crawler::crawl_one_page_urls(client, &url).await {
+            Ok(urls) => {
+                checkpoint.failed_urls.remove(&url);
+                article_urls.extend(urls.into_iter().filter(|u| !checkpoint.is_completed(u)));
+            }
+            Err(e) => eprintln!("Error: Failed to crawl {} with error\n{:#?}", url, e),
+        }
+    }
+    crawl_urls_with_checkpoint(
+        client,
+        checkpoint,
+        checkpoint_path,
+        article_urls,
+        concurrency,
+        output,
+    )
+    .await;
+    save_checkpoint(checkpoint, checkpoint_path);
+
+    while let Some(range) = checkpoint.remaining_range() {
+        let page = *range.start();
+        let page_url = crawler::compose_page_url(&checkpoint.board, page);
+        match crawler::crawl_one_page_urls(client, &page_url).await {
+            Ok(urls) => {
+                let urls = urls
+                    .into_iter()
+                    .filter(|u| !checkpoint.is_completed(u))
+                    .collect();
+                crawl_urls_with_checkpoint(
+                    client,
+                    checkpoint,
+                    checkpoint_path,
+                    urls,
+                    concurrency,
+                    output,
+                )
+                .await;
+            }
+            Err(e) => {
+                eprintln!("Error: Failed to crawl {} with error\n{:#?}", page_url, e);
+                checkpoint.fail_url(&page_url);
+            }
+        }
+        checkpoint.complete_page(page);
+        save_checkpoint(checkpoint, checkpoint_path);
+    }
+}
+
+/// Crawl the articles of given URLs, and save the checkpoint right after each
+/// of them is written, so that no article is written twice after resuming.
+async fn crawl_urls_with_checkpoint(
+    client: &Client,
+    checkpoint: &mut Checkpoint,
+    checkpoint_path: &Path,
+    urls: Vec<String>,
+    concurrency: usize,
+    output: &mut Output,
+) {
+    let mut articles = crawler::crawl_urls_stream(client, urls, concurrency).boxed_local();
+    while let Some((url, result)) = articles.next().await {
+        match result {
+            Ok(article) => {
+                output.write(&article);
+                checkpoint.complete_article(&url);
+            }
+            Err(e) => {
+                eprintln!("Error: Failed to crawl {} with error\n{:#?}", url, e);
+                checkpoint.fail_url(&url);
+            }
+        }
+        save_checkpoint(checkpoint, checkpoint_path);
+    }
+}
+
+fn save_checkpoint(checkpoint: &Checkpoint, path: &Path) {
+    checkpoint.save(path).unwrap_or_else(|e| {
+        eprintln!(
+            "Error: Failed to save checkpoint {}\n{:#?}",
+            path.display(),
+            e
+        );
+        process::exit(1)
+    });
+}
+
+/// Poll every board once, and write the articles not seen before.
+/// Articles failed to crawl are not recorded, so they are crawled again in next poll.
+async fn watch_boards(
+    client: &Client,
+    boards: &[Board],
+    seen: &mut SeenState,
+    concurrency: usize,
+    output: &mut Output,
+) {
+    for board in boards {
+        let seen_ids: HashSet<String> = seen.ids(board).into_iter().collect();
+        let urls = match crawler::crawl_unseen_urls(client, board, &seen_ids).await {
+            Ok(urls) => urls,
+            Err(e) => {
+                eprintln!(
+                    "Error: Failed to crawl board {} with error\n{:#?}",
+                    board, e
+                );
+                continue;
+            }
+        };
+        let mut articles = crawler::crawl_urls_stream(client, urls, concurrency).boxed_local();
+        while let Some((url, result)) = articles.next().await {
+            match result {
+                Ok(article) => {
+                    output.write(&article);
+                    seen.insert(board, &article.meta.id);
+                }
+                Err(e) => eprintln!("Error: Failed to crawl {} with error\n{:#?}", url, e),
+            }
+        }
+    }
+    seen.prune(MAX_SEEN_IDS);
+}
+
+/// Complete when SIGINT or SIGTERM is received.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate()).unwrap_or_else(|e| {
+            eprintln!("Error: Failed to listen to SIGTERM\n{:#?}", e);
             process::exit(1)
         });
-    } else {
-        println!("Results in JSON format:\n{}", json_output);
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
     }
 }
 
+fn save_seen_state(seen: &SeenState, path: &Path) {
+    seen.save(path).unwrap_or_else(|e| {
+        eprintln!(
+            "Error: Failed to save seen state {}\n{:#?}",
+            path.display(),
+            e
+        );
+        process::exit(1)
+    });
+}
+
+/// Replace the file of articles with given articles in given format.
+fn save_articles(path: &Path, articles: &[Article], format: &OutputFormat) -> io::Result<()> {
+    json_file::replace_file(path, |writer| {
+        match format {
+            OutputFormat::Json => serde_json::to_writer_pretty(writer, articles)?,
+            OutputFormat::JsonLines => {
+                for article in articles {
+                    serde_json::to_writer(&mut *writer, article)?;
+                    writer.write_all(b"\n")?;
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Load articles from a file in either JSON or JSON Lines format, and
+/// return them with the format of the file.
+fn load_articles(path: &Path) -> io::Result<(Vec<Article>, OutputFormat)> {
+    let mut text = String::new();
+    BufReader::new(File::open(path)?).read_to_string(&mut text)?;
+    if text.trim_start().starts_with('[') {
+        return Ok((serde_json::from_str(&text)?, OutputFormat::Json));
+    }
+    let mut articles = vec![];
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        articles.push(serde_json::from_str(line)?);
+    }
+    Ok((articles, OutputFormat::JsonLines))
+}
+
+fn print_category(category: &BoardCategory, indent: usize) {
+    for board in category.boards.iter() {
+        print_board(board, indent);
+    }
+    for sub_category in category.categories.iter() {
+        println!(
+            "{}{}\t{}",
+            "  ".repeat(indent),
+            sub_category.info.name,
+            sub_category.info.title
+        );
+        print_category(sub_category, indent + 1);
+    }
+}
+
+fn print_board(board: &BoardInfo, indent: usize) {
+    println!(
+        "{}{}\t{}\t{}\t{}",
+        "  ".repeat(indent),
+        board.name,
+        board.category,
+        board.title,
+        board.user_count.map(|c| c.to_string()).unwrap_or_default()
+    );
+}
+
+/// Parse a date like "2020-03-01" into the beginning of the date in Taiwan.
+fn parse_date(date: &str) -> Result<DateTime<FixedOffset>, String> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date \"{}\": {}", date, e))?;
+    Ok(FixedOffset::east(8 * 3600)
+        .from_local_datetime(&date.and_hms(0, 0, 0))
+        .unwrap())
+}
+
+fn parse_board(board: PathBuf) -> Board {
+    let board_string = board.into_os_string().into_string().unwrap();
+    board_string.parse::<Board>().unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    })
+}
+
 async fn create_client(
     user_agent: Option<String>,
     proxies: Option<Vec<Proxy>>,
     connect_timeout: u64,
+    rate_limiter: Option<RateLimiter>,
+    retry_policy: RetryPolicy,
 ) -> Client {
     match crawler::create_client(
         user_agent,
@@ -200,7 +1261,7 @@ async fn create_client(
     )
     .await
     {
-        Ok(client) => client,
+        Ok(client) => client.rate_limiter(rate_limiter).retry_policy(retry_policy),
         Err(e) => {
             eprintln!("Error: Failed to create client\n({:#?})", e);
             process::exit(1);
@@ -4,11 +4,24 @@ use chrono::{offset::FixedOffset, offset::LocalResult, prelude::*, DateTime};
 use regex::Regex;
 use select::predicate::{Attr, Class, Name, Predicate};
 use select::{document::Document, node::Node};
+use url::Url;
 
-use crate::article::{Article, BoardName, Meta, Reply, ReplyCount, ReplyType};
+use crate::article::{
+    self, Article, Board, BoardEntry, BoardInfo, BoardListingEntry, BoardName, CategoryInfo,
+    ContentBody, Crosspost, CrosspostSource, Deletion, Edit, FeedEntry, Link, LinkKind,
+    ManListingEntry, Meta, ModerationAction, ModerationEvent, PushCount, Quote, Reply, ReplyCount,
+    ReplyType,
+};
 
 lazy_static! {
     static ref TW_TIME_OFFSET: FixedOffset = FixedOffset::east(8 * 3600);
+    static ref CROSSPOST_SOURCE_RE: Regex = Regex::new(
+        r"^\s*※ \[本文轉錄自\s*(?P<board>[\w\-]+)\s*看板\s*(#(?P<aid>[0-9A-Za-z\-_]+))?\s*\]"
+    )
+    .unwrap();
+    static ref PTT_URL: Url = Url::parse("https://www.ptt.cc/").unwrap();
+    static ref QUOTE_HEADER_RE: Regex =
+        Regex::new(r"^※ 引述\s*《\s*(?P<id>\w+)?[^》]*》之銘言").unwrap();
 }
 
 /// Error represents the errors which might occur when parsing.
@@ -20,34 +33,36 @@ pub enum Error {
 }
 
 pub fn parse(document: &Document) -> Result<Article, Error> {
-    if !is_article_exist(&document) {
+    if !is_article_exist(document) {
         warn!("article deleted");
         return Err(Error::DeletedArticle);
     }
 
-    let meta = parse_meta(&document)?;
-    let content = parse_content(&document)?;
-    let replies = parse_replies(&document, meta.date);
-
-    let reply_count = ReplyCount {
-        push: replies
-            .iter()
-            .filter(|r| r.reply_type == ReplyType::Push)
-            .count() as i16,
-        neutral: replies
-            .iter()
-            .filter(|r| r.reply_type == ReplyType::Neutral)
-            .count() as i16,
-        boo: replies
-            .iter()
-            .filter(|r| r.reply_type == ReplyType::Boo)
-            .count() as i16,
-    };
+    let meta = parse_meta(document)?;
+    let content = parse_content(document)?;
+    let quoted_content = parse_quoted_content(document);
+    let body = parse_content_body(&quoted_content);
+    let links = parse_links(
+        document.find(Attr("id", "main-content").descendant(Name("a"))),
+        &quoted_content,
+    );
+    let (replies, moderation_events) = parse_replies(document, meta.date);
+    let edits = parse_edits(document, meta.date);
+    let crossposts = parse_crossposts(document, meta.date);
+    let crossposted_from = parse_crosspost_source(document);
+
+    let reply_count = ReplyCount::from_replies(&replies);
     Ok(Article {
         meta,
         content,
+        body,
+        links,
         reply_count,
         replies,
+        edits,
+        crossposts,
+        crossposted_from,
+        moderation_events,
     })
 }
 
@@ -134,10 +149,7 @@ fn parse_title(document: &Document) -> Result<(Option<String>, String), Error> {
     let trim_title = original_title.trim();
     Ok(match RE.captures(trim_title) {
         Some(cap) => (
-            match cap.name("category") {
-                Some(m) => Some(m.as_str().to_owned()),
-                None => None,
-            },
+            cap.name("category").map(|m| m.as_str().to_owned()),
             cap["title"].to_owned(),
         ),
         None => (None, trim_title.to_owned()),
@@ -186,7 +198,7 @@ fn parse_author(document: &Document) -> Result<(String, Option<String>), Error>
     }
 }
 
-fn parse_board(document: &Document) -> Result<BoardName, Error> {
+fn parse_board(document: &Document) -> Result<Board, Error> {
     let board = match document
         .find(Name("span").and(Class("article-meta-value")))
         .nth(1)
@@ -204,7 +216,10 @@ fn parse_board(document: &Document) -> Result<BoardName, Error> {
             board_node.unwrap().next().unwrap().text()
         }
     };
-    Ok(board.parse::<BoardName>().unwrap_or(BoardName::Unknown))
+    Ok(board.trim().parse::<Board>().unwrap_or_else(|e| {
+        warn!("{}", e);
+        BoardName::Unknown.into()
+    }))
 }
 
 fn parse_date(document: &Document) -> Result<DateTime<FixedOffset>, Error> {
@@ -299,7 +314,12 @@ fn get_main_content(document: &Document) -> String {
 }
 
 fn parse_content(document: &Document) -> Result<String, Error> {
-    let main_content = get_main_content(document);
+    find_content(&get_main_content(document), |_| false)
+}
+
+/// Find content in main content, which ends at the first line starting with "※"
+/// and not skipped, or at the first line starting with "※" if all of them are skipped.
+fn find_content<F: Fn(&str) -> bool>(main_content: &str, skip: F) -> Result<String, Error> {
     let content_start_index = match main_content.find('\n') {
         Some(start_index) => start_index,
         None => {
@@ -307,8 +327,20 @@ fn parse_content(document: &Document) -> Result<String, Error> {
             return Err(Error::InvalidFormat);
         }
     };
-    let content_end_index = match main_content[(content_start_index + 1)..].find("\n※") {
-        Some(end_index) => end_index + content_start_index + 1,
+    // header of forwarded article is a part of content
+    let search_start_index = match CROSSPOST_SOURCE_RE.find(&main_content[content_start_index..]) {
+        Some(m) => content_start_index + m.end(),
+        None => content_start_index + 1,
+    };
+    let mut end_indexes = main_content[search_start_index..]
+        .match_indices("\n※")
+        .map(|(i, _)| i + search_start_index);
+    let first_end_index = end_indexes.clone().next();
+    let content_end_index = match end_indexes
+        .find(|&i| !skip(&main_content[(i + 1)..]))
+        .or(first_end_index)
+    {
+        Some(end_index) => end_index,
         None => {
             error!("Failed to find end of content");
             return Err(Error::InvalidFormat);
@@ -318,11 +350,113 @@ fn parse_content(document: &Document) -> Result<String, Error> {
     Ok(content.trim().to_owned())
 }
 
-fn parse_replies(document: &Document, article_time: Option<DateTime<FixedOffset>>) -> Vec<Reply> {
-    document
-        .find(Name("div").and(Class("push")))
-        .flat_map(|n| parse_reply(&n, article_time))
-        .collect::<Vec<Reply>>()
+/// Parse content with all quotations. Unlike content, it does not end at
+/// headers of quotation like "※ 引述《id》之銘言".
+fn parse_quoted_content(document: &Document) -> String {
+    find_content(&get_main_content(document), |line| {
+        QUOTE_HEADER_RE.is_match(line)
+    })
+    .unwrap_or_default()
+}
+
+/// Separate content into author's text, quotations and signature.
+/// Header lines of articles without meta, e.g. "作者: someone", are skipped.
+fn parse_content_body(content: &str) -> ContentBody {
+    lazy_static! {
+        static ref HEADER_RE: Regex = Regex::new(r"^(作者|標題|時間|看板|站內)\s*[:：]").unwrap();
+    }
+
+    let mut lines: Vec<&str> = content
+        .lines()
+        .skip_while(|l| HEADER_RE.is_match(l))
+        .collect();
+    // separator before the line "※ 發信站"
+    if lines.last().map(|l| l.trim()) == Some("--") {
+        lines.pop();
+    }
+    let signature = match lines.iter().rposition(|l| l.trim() == "--") {
+        Some(i) => {
+            let signature = lines.split_off(i)[1..].join("\n").trim().to_owned();
+            Some(signature).filter(|s| !s.is_empty())
+        }
+        None => None,
+    };
+
+    let mut text_lines: Vec<&str> = vec![];
+    let mut quotes: Vec<Quote> = vec![];
+    let mut in_quote = false;
+    for line in lines {
+        if let Some(cap) = QUOTE_HEADER_RE.captures(line.trim()) {
+            quotes.push(Quote {
+                author_id: cap.name("id").map(|m| m.as_str().to_owned()),
+                lines: vec![],
+            });
+            in_quote = true;
+        } else if line == ":" || line.starts_with(": ") {
+            if !in_quote {
+                quotes.push(Quote {
+                    author_id: None,
+                    lines: vec![],
+                });
+                in_quote = true;
+            }
+            let quoted = line.strip_prefix(": ").unwrap_or("");
+            quotes.last_mut().unwrap().lines.push(quoted.to_owned());
+        } else {
+            text_lines.push(line);
+            in_quote = false;
+        }
+    }
+    ContentBody {
+        text: text_lines.join("\n").trim().to_owned(),
+        quotes,
+        signature,
+    }
+}
+
+/// Parse replies and actions of moderator among them in order, so that
+/// position of every action is the number of replies before it.
+fn parse_replies(
+    document: &Document,
+    article_time: Option<DateTime<FixedOffset>>,
+) -> (Vec<Reply>, Vec<ModerationEvent>) {
+    let mut replies = vec![];
+    let mut events = vec![];
+    let push_or_notice = Name("div")
+        .and(Class("push"))
+        .or(Name("span").and(Class("f2")));
+    for node in document.find(push_or_notice) {
+        if node.is(Class("push")) {
+            if let Ok(reply) = parse_reply(&node, article_time) {
+                replies.push(reply);
+            }
+        } else if let Some(event) = parse_moderation_event(&node.text(), replies.len()) {
+            events.push(event);
+        }
+    }
+    (replies, events)
+}
+
+/// Parse an action of moderator in format of "※hateOnas 於 01/06/2014 18:38:19 將推薦值歸零".
+fn parse_moderation_event(text: &str, position: usize) -> Option<ModerationEvent> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^※\s*(?P<id>\w+) 於 (?P<date>\d{2}/\d{2}/\d{4} \d{2}:\d{2}:\d{2}) (?P<action>.+)$"
+        )
+        .unwrap();
+    }
+
+    let cap = RE.captures(text.trim())?;
+    let action = match cap["action"].trim() {
+        "將推薦值歸零" => ModerationAction::ResetPushCount,
+        other => ModerationAction::Other(other.to_owned()),
+    };
+    Some(ModerationEvent {
+        position,
+        moderator_id: cap["id"].to_owned(),
+        date: parse_date_from_str(&cap["date"], "%m/%d/%Y %H:%M:%S").ok(),
+        action,
+    })
 }
 
 fn parse_reply(node: &Node, article_time: Option<DateTime<FixedOffset>>) -> Result<Reply, Error> {
@@ -351,12 +485,13 @@ fn parse_reply(node: &Node, article_time: Option<DateTime<FixedOffset>>) -> Resu
         .next()
         .unwrap()
         .text();
-    let mut content = node
+    let content_node = node
         .find(Name("span").and(Class("push-content")))
         .next()
-        .unwrap()
+        .unwrap();
+    let mut content = content_node
         .text()
-        .trim_start_matches(|c| (c == ':' || c == ' '))
+        .trim_start_matches(&[':', ' '][..])
         .trim()
         .to_owned();
     let mut ip_and_time = node
@@ -424,30 +559,514 @@ fn parse_reply(node: &Node, article_time: Option<DateTime<FixedOffset>>) -> Resu
         }
     });
 
+    let links = parse_links(content_node.find(Name("a")), &content);
     Ok(Reply {
         author_id,
         reply_type,
         ip,
         date,
         content,
+        links,
+    })
+}
+
+/// Extract links from anchors and bare URLs in text, in the order they appear
+/// in text. Anchors whose text is not in given text, e.g. the URL of article
+/// itself and embedded previews, are skipped.
+fn parse_links<'a, I: Iterator<Item = Node<'a>>>(anchors: I, text: &str) -> Vec<Link> {
+    lazy_static! {
+        static ref URL_RE: Regex =
+            Regex::new(r"https?://[0-9A-Za-z\-._~:/?#\[\]@!$&'*+,;=%]+").unwrap();
+        static ref AID_RE: Regex =
+            Regex::new(r"#(?P<aid>[0-9A-Za-z\-_]{8})\s*\((?P<board>[\w\-]+)\)").unwrap();
+    }
+
+    // text of anchors are auto-linked URLs or AIDs, while their hrefs are full URLs
+    let mut found: Vec<(usize, usize, Link)> = vec![];
+    for anchor in anchors {
+        let anchor_text = anchor.text();
+        let anchor_text = anchor_text.trim();
+        let (start, href) = match (text.find(anchor_text), anchor.attr("href")) {
+            (Some(start), Some(href)) if !anchor_text.is_empty() => (start, href),
+            _ => continue,
+        };
+        let url = match PTT_URL.join(href) {
+            Ok(url) => url.to_string(),
+            Err(_) => continue,
+        };
+        found.push((
+            start,
+            start + anchor_text.len(),
+            Link {
+                kind: classify_link(&url),
+                url,
+            },
+        ));
+    }
+    let is_covered = |found: &[(usize, usize, Link)], start: usize| {
+        found.iter().any(|(s, e, _)| *s <= start && start < *e)
+    };
+    for m in URL_RE.find_iter(text) {
+        if !is_covered(&found, m.start()) {
+            let url = m.as_str().to_owned();
+            found.push((
+                m.start(),
+                m.end(),
+                Link {
+                    kind: classify_link(&url),
+                    url,
+                },
+            ));
+        }
+    }
+    for cap in AID_RE.captures_iter(text) {
+        let m = cap.get(0).unwrap();
+        if is_covered(&found, m.start()) {
+            continue;
+        }
+        let board = match cap["board"].parse::<Board>() {
+            Ok(board) => board,
+            Err(_) => continue,
+        };
+        if let Some(id) = article::id_from_aid(&cap["aid"]) {
+            found.push((
+                m.start(),
+                m.end(),
+                Link {
+                    url: format!("{}bbs/{}/{}.html", *PTT_URL, board, id),
+                    kind: LinkKind::PttArticle {
+                        board,
+                        id,
+                        aid: Some(cap["aid"].to_owned()),
+                    },
+                },
+            ));
+        }
+    }
+
+    found.sort_by_key(|(start, _, _)| *start);
+    let mut links: Vec<Link> = vec![];
+    for (_, _, link) in found {
+        if !links.iter().any(|l| l.url == link.url) {
+            links.push(link);
+        }
+    }
+    links
+}
+
+/// Classify the link by its host and path.
+fn classify_link(url: &str) -> LinkKind {
+    const IMAGE_HOSTS: [&str; 4] = ["imgur.com", "i.redd.it", "pbs.twimg.com", "i.ibb.co"];
+    const IMAGE_EXTENSIONS: [&str; 5] = [".jpg", ".jpeg", ".png", ".gif", ".webp"];
+
+    let parsed_url = match Url::parse(url) {
+        Ok(parsed_url) => parsed_url,
+        Err(_) => return LinkKind::Other,
+    };
+    let host = parsed_url.host_str().unwrap_or("");
+    let host = host
+        .strip_prefix("www.")
+        .or_else(|| host.strip_prefix("m."))
+        .unwrap_or(host);
+    let path = parsed_url.path().to_lowercase();
+    match host {
+        "youtube.com" | "youtu.be" => LinkKind::YouTube,
+        "ptt.cc" => {
+            let segments: Vec<&str> = parsed_url.path_segments().map_or(vec![], |s| s.collect());
+            match segments.as_slice() {
+                ["bbs", board, file] if file.ends_with(".html") => {
+                    let id = file.trim_end_matches(".html");
+                    match (board.parse::<Board>(), article::aid_from_id(id)) {
+                        (Ok(board), Some(aid)) => LinkKind::PttArticle {
+                            board,
+                            id: id.to_owned(),
+                            aid: Some(aid),
+                        },
+                        _ => LinkKind::Other,
+                    }
+                }
+                _ => LinkKind::Other,
+            }
+        }
+        _ if IMAGE_HOSTS
+            .iter()
+            .any(|h| host == *h || host.ends_with(&format!(".{}", h))) =>
+        {
+            LinkKind::Image
+        }
+        _ if IMAGE_EXTENSIONS.iter().any(|e| path.ends_with(e)) => LinkKind::Image,
+        _ => LinkKind::Other,
+    }
+}
+
+/// Parse the records of editing article, which are in either format of
+/// "※ 編輯: id 來自: 1.2.3.4 (08/11 22:31)" or "※ 編輯: id (1.2.3.4 臺灣), 06/10/2018 09:25:49".
+/// Year of the former format is resolved from the date of article.
+fn parse_edits(document: &Document, article_time: Option<DateTime<FixedOffset>>) -> Vec<Edit> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^※ 編輯:\s*(?P<id>\w+)\s*(來自:\s*(?P<from>\S+)\s*\((?P<short_date>\d{2}/\d{2} \d{2}:\d{2})\)|\((?P<ip>[^\s)]+)[^)]*\),\s*(?P<date>\d{2}/\d{2}/\d{4} \d{2}:\d{2}:\d{2}))"
+        )
+        .unwrap();
+    }
+
+    get_main_content(document)
+        .lines()
+        .flat_map(|line| RE.captures(line.trim()))
+        .map(|cap| {
+            let ip = cap
+                .name("from")
+                .or_else(|| cap.name("ip"))
+                .and_then(|m| m.as_str().parse::<Ipv4Addr>().ok());
+            let date = match (cap.name("date"), cap.name("short_date")) {
+                (Some(m), _) => parse_date_from_str(m.as_str(), "%m/%d/%Y %H:%M:%S").ok(),
+                (None, Some(m)) => article_time.and_then(|t| resolve_short_date(m.as_str(), t)),
+                (None, None) => None,
+            };
+            Edit {
+                editor_id: cap["id"].to_owned(),
+                ip,
+                date,
+            }
+        })
+        .collect()
+}
+
+/// Parse the records of forwarding article to other boards, which are in
+/// format of "※ mitami:轉錄至看板 BeautyBody  04/02 12:23".
+fn parse_crossposts(
+    document: &Document,
+    article_time: Option<DateTime<FixedOffset>>,
+) -> Vec<Crosspost> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^※ (?P<user>\w+):轉錄至看板 (?P<board>\S+)\s+(?P<date>\d{2}/\d{2} \d{2}:\d{2})"
+        )
+        .unwrap();
+    }
+
+    get_main_content(document)
+        .lines()
+        .flat_map(|line| RE.captures(line.trim()))
+        .flat_map(|cap| {
+            let board = match cap["board"].parse::<Board>() {
+                Ok(board) => board,
+                Err(e) => {
+                    warn!("{}", e);
+                    return None;
+                }
+            };
+            Some(Crosspost {
+                user_id: cap["user"].to_owned(),
+                board,
+                date: article_time.and_then(|t| resolve_short_date(&cap["date"], t)),
+            })
+        })
+        .collect()
+}
+
+/// Parse the header "※ [本文轉錄自 Gossiping 看板 #1VDrjZHu ]" of article
+/// forwarded from another board.
+fn parse_crosspost_source(document: &Document) -> Option<CrosspostSource> {
+    let main_content = get_main_content(document);
+    let content_start_index = main_content.find('\n')?;
+    let cap = CROSSPOST_SOURCE_RE.captures(&main_content[content_start_index..])?;
+    let aid = cap.name("aid").map(|m| m.as_str().to_owned());
+    Some(CrosspostSource {
+        board: cap["board"].parse::<Board>().ok()?,
+        id: aid.as_deref().and_then(article::id_from_aid),
+        aid,
     })
 }
 
+/// Resolve a date without year like "08/11 22:31" to the first such date not
+/// earlier than the day of article.
+fn resolve_short_date(
+    date_str: &str,
+    article_time: DateTime<FixedOffset>,
+) -> Option<DateTime<FixedOffset>> {
+    let mut year = article_time.year();
+    let article_day = article_time.date().and_hms(0, 0, 0);
+    // a few years are enough to find Feb 29th
+    for _ in 0..8 {
+        let date = parse_date_from_str(&format!("{}/{}", year, date_str), "%Y/%m/%d %H:%M");
+        match date {
+            Ok(date) if date >= article_day => return Some(date),
+            _ => year += 1,
+        }
+    }
+    None
+}
+
+/// Parse the entries of articles listed in an index page of board.
+/// URLs of articles are prefixed with `base_url`, and entries are marked with
+/// given page number.
+pub fn parse_listing(
+    document: &Document,
+    base_url: &str,
+    page: Option<u32>,
+) -> Vec<BoardListingEntry> {
+    let mut pinned = false;
+    let mut entries = vec![];
+    let list = match document.find(Class("r-list-container")).next() {
+        Some(n) => n,
+        None => return entries,
+    };
+    for node in list.children() {
+        if node.is(Class("r-list-sep")) {
+            // articles below the separator are pinned
+            pinned = true;
+        } else if node.is(Class("r-ent")) {
+            let mut entry = parse_listing_entry(&node, base_url, pinned);
+            entry.page = page;
+            entry.position = entries.len();
+            entries.push(entry);
+        }
+    }
+    entries
+}
+
+fn parse_listing_entry(node: &Node, base_url: &str, pinned: bool) -> BoardListingEntry {
+    let text_of = |class: &str| {
+        node.find(Class(class))
+            .next()
+            .map(|n| n.text().trim().to_owned())
+            .unwrap_or_default()
+    };
+    let push_count = text_of("nrec").parse::<PushCount>().unwrap_or_else(|_| {
+        warn!("Invalid push count {:?}", text_of("nrec"));
+        PushCount::Count(0)
+    });
+    let mark = Some(text_of("mark")).filter(|m| !m.is_empty());
+    let link = node
+        .find(Class("title").descendant(Name("a")))
+        .find(|a| !a.text().trim().is_empty());
+    let (title, url) = match link {
+        Some(a) => (
+            a.text().trim().to_owned(),
+            a.attr("href").map(|href| format!("{}{}", base_url, href)),
+        ),
+        None => (text_of("title"), None),
+    };
+    let deletion = match url {
+        Some(_) => None,
+        None => Some(parse_deletion(&title)),
+    };
+
+    BoardListingEntry {
+        push_count,
+        mark,
+        date: text_of("date"),
+        author_id: text_of("author"),
+        title,
+        url,
+        pinned,
+        page: None,
+        position: 0,
+        deletion,
+    }
+}
+
+/// Parse the title of deleted article like "(本文已被刪除) [author]" or
+/// "(已被moderator刪除) <author>".
+fn parse_deletion(title: &str) -> Deletion {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^\((本文已被刪除|已被(?P<by>\w+)刪除)\)\s*([\[<](?P<author>\w+)[\]>])?")
+                .unwrap();
+    }
+
+    match RE.captures(title) {
+        Some(cap) => {
+            let author_id = cap.name("author").map(|m| m.as_str().to_owned());
+            let deleted_by = cap
+                .name("by")
+                .map(|m| m.as_str().to_owned())
+                .or_else(|| author_id.clone());
+            Deletion {
+                deleted_by,
+                author_id,
+            }
+        }
+        None => {
+            warn!("Unknown format of deleted article {:?}", title);
+            Deletion {
+                deleted_by: None,
+                author_id: None,
+            }
+        }
+    }
+}
+
+/// Parse the boards and categories listed in hot boards or classification page.
+/// URLs are prefixed with `base_url`.
+pub fn parse_board_entries(document: &Document, base_url: &str) -> Vec<BoardEntry> {
+    document
+        .find(Name("a").and(Class("board")))
+        .flat_map(|node| {
+            let text_of = |class: &str| {
+                node.find(Class(class))
+                    .next()
+                    .map(|n| n.text().trim().to_owned())
+                    .unwrap_or_default()
+            };
+            let href = node.attr("href")?;
+            let url = format!("{}{}", base_url, href);
+            let name = text_of("board-name");
+            let title = text_of("board-title");
+            if href.starts_with("/cls/") {
+                return Some(BoardEntry::Category(CategoryInfo { name, title, url }));
+            }
+            let name = match name.parse::<Board>() {
+                Ok(board) => board,
+                Err(e) => {
+                    warn!("{}", e);
+                    return None;
+                }
+            };
+            Some(BoardEntry::Board(BoardInfo {
+                name,
+                title,
+                category: text_of("board-class"),
+                user_count: text_of("board-nuser").parse::<u32>().ok(),
+                url,
+            }))
+        })
+        .collect()
+}
+
+/// Parse the directories and articles listed in a page of digest (精華區).
+/// Entries without link, e.g. deleted articles, are skipped.
+pub fn parse_man_entries(document: &Document, base_url: &str) -> Vec<ManListingEntry> {
+    document
+        .find(Class("m-ent"))
+        .flat_map(|entry| {
+            let link = entry.find(Class("title").descendant(Name("a"))).next()?;
+            let href = link.attr("href")?;
+            Some(ManListingEntry {
+                title: link.text().trim().to_owned(),
+                url: format!("{}{}", base_url, href),
+                is_directory: href.ends_with("/index.html"),
+            })
+        })
+        .collect()
+}
+
+/// Parse the entries of Atom feed of board. Entries without valid link of
+/// article are skipped.
+pub fn parse_feed(document: &Document) -> Vec<FeedEntry> {
+    document
+        .find(Name("entry"))
+        .flat_map(|entry| {
+            let text_of = |name: &str| {
+                entry
+                    .find(Name(name))
+                    .next()
+                    .map(|n| n.text().trim().to_owned())
+            };
+            let date_of =
+                |name: &str| text_of(name).and_then(|t| DateTime::parse_from_rfc3339(&t).ok());
+            let url = entry
+                .find(Name("link"))
+                .next()
+                .and_then(|n| n.attr("href"))
+                .map(|href| href.to_owned())
+                .or_else(|| text_of("id"))?;
+            let id = article::id_from_url(&url)?.to_owned();
+            // name of author might be followed by nickname
+            let author_id = text_of("name")
+                .and_then(|name| name.split_whitespace().next().map(|s| s.to_owned()))
+                .unwrap_or_default();
+            Some(FeedEntry {
+                id,
+                title: text_of("title").unwrap_or_default(),
+                author_id,
+                published: date_of("published"),
+                updated: date_of("updated"),
+                url,
+            })
+        })
+        .collect()
+}
+
 fn is_leap_year(year: i32) -> bool {
     (year % 4 == 0) && (year % 100 != 0 || year % 400 == 0)
 }
 
 #[cfg(test)]
+#[allow(
+    clippy::collapsible_match,
+    clippy::match_like_matches_macro,
+    clippy::needless_range_loop,
+    clippy::zero_prefixed_literal
+)]
 mod tests {
     use pretty_assertions::assert_eq;
     use select::document::Document;
 
+    use crate::article::ReplyUpdate;
+
     use super::*;
 
     fn load_document(path: &str) -> Document {
         Document::from(load_str!(path))
     }
 
+    const LISTING_HTML: &str = r#"
+<div class="r-list-container action-bar-margin bbs-screen">
+  <div class="search-bar"></div>
+  <div class="r-ent">
+    <div class="nrec"><span class="hl f1">爆</span></div>
+    <div class="title">
+      <a href="/bbs/Gossiping/M.1597463395.A.478.html">[問卦] 有沒有八卦</a>
+    </div>
+    <div class="meta">
+      <div class="author">someone</div>
+      <div class="article-menu"></div>
+      <div class="date"> 8/15</div>
+      <div class="mark">M</div>
+    </div>
+  </div>
+  <div class="r-ent">
+    <div class="nrec"><span class="hl f0">X2</span></div>
+    <div class="title">
+      (本文已被刪除) [another]
+    </div>
+    <div class="meta">
+      <div class="author">-</div>
+      <div class="article-menu"></div>
+      <div class="date"> 8/15</div>
+      <div class="mark"></div>
+    </div>
+  </div>
+  <div class="r-ent">
+    <div class="nrec"></div>
+    <div class="title">
+      (已被moderator刪除) &lt;writer&gt;
+    </div>
+    <div class="meta">
+      <div class="author">-</div>
+      <div class="article-menu"></div>
+      <div class="date"> 8/15</div>
+      <div class="mark"></div>
+    </div>
+  </div>
+  <div class="r-list-sep"></div>
+  <div class="r-ent">
+    <div class="nrec"></div>
+    <div class="title">
+      <a href="/bbs/Gossiping/M.1512345678.A.123.html">[公告] 板規</a>
+    </div>
+    <div class="meta">
+      <div class="author">moderator</div>
+      <div class="article-menu"></div>
+      <div class="date">12/04</div>
+      <div class="mark">!</div>
+    </div>
+  </div>
+</div>
+"#;
+
     #[test]
     fn test_deleted_article() {
         let documents = load_document("../tests/Gossiping_M.1577579359.A.B76.html");
@@ -551,14 +1170,29 @@ mod tests {
     fn test_parse_board() {
         let documents = load_document("../tests/Soft_Job_M.1181801925.A.86E.html");
 
-        assert_eq!(parse_board(&documents).unwrap(), BoardName::SoftJob);
+        assert_eq!(
+            parse_board(&documents).unwrap(),
+            "Soft_Job".parse::<Board>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_board() {
+        let documents = Document::from(
+            r#"<div id="main-content"><div class="article-metaline"><span class="article-meta-tag">作者</span><span class="article-meta-value">someone (某人)</span></div><div class="article-metaline-right"><span class="article-meta-tag">看板</span><span class="article-meta-value">不存在的看板</span></div></div>"#,
+        );
+
+        assert_eq!(parse_board(&documents).unwrap(), BoardName::Unknown.into());
     }
 
     #[test]
     fn test_parse_board_not_in_html_meta() {
         let documents = load_document("../tests/Gossiping_M.1123769450.A.A1A.html");
 
-        assert_eq!(parse_board(&documents).unwrap(), BoardName::Gossiping);
+        assert_eq!(
+            parse_board(&documents).unwrap().known(),
+            Some(BoardName::Gossiping)
+        );
     }
 
     #[test]
@@ -600,7 +1234,7 @@ mod tests {
                 .and_hms(14, 18, 43),
         );
 
-        assert_eq!(parse_replies(&documents, article_date).len(), 5)
+        assert_eq!(parse_replies(&documents, article_date).0.len(), 5)
     }
 
     #[test]
@@ -613,7 +1247,7 @@ mod tests {
                 .and_hms(7, 11, 31),
         );
 
-        assert_eq!(parse_replies(&documents, article_date).len(), 1491)
+        assert_eq!(parse_replies(&documents, article_date).0.len(), 1491)
     }
 
     #[test]
@@ -626,13 +1260,58 @@ mod tests {
                 .and_hms(18, 9, 31),
         );
 
-        let replies = parse_replies(&documents, article_date);
+        let (replies, _) = parse_replies(&documents, article_date);
 
         for i in 0..=5 {
             assert_eq!(replies[i].date, None);
         }
     }
 
+    #[test]
+    fn test_parse_moderation_events() {
+        let documents = load_document("../tests/Gossiping_M.1119222611.A.7A9.html");
+        let (_, events) = parse_replies(&documents, parse_date(&documents).ok());
+
+        assert_eq!(
+            events.iter().map(|e| e.position).collect::<Vec<_>>(),
+            vec![249, 288, 436, 624, 767]
+        );
+        assert_eq!(
+            events[0],
+            ModerationEvent {
+                position: 249,
+                moderator_id: "hateOnas".to_owned(),
+                date: Some(
+                    FixedOffset::east(8 * 3600)
+                        .ymd(2014, 1, 6)
+                        .and_hms(18, 38, 19)
+                ),
+                action: ModerationAction::ResetPushCount,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_other_moderation_event() {
+        assert_eq!(
+            parse_moderation_event("※ someone 於 08/15/2020 12:00:00 將文章鎖定", 3),
+            Some(ModerationEvent {
+                position: 3,
+                moderator_id: "someone".to_owned(),
+                date: Some(
+                    FixedOffset::east(8 * 3600)
+                        .ymd(2020, 8, 15)
+                        .and_hms(12, 0, 0)
+                ),
+                action: ModerationAction::Other("將文章鎖定".to_owned()),
+            })
+        );
+        assert_eq!(
+            parse_moderation_event("※ 發信站: 批踢踢實業坊(ptt.cc)", 0),
+            None
+        );
+    }
+
     #[test]
     fn test_parse_article_without_reply() {
         let documents = load_document("../tests/Soft_Job_M.1181804025.A.7A7.html");
@@ -642,7 +1321,7 @@ mod tests {
                 .and_hms(14, 53, 44),
         );
 
-        assert_eq!(parse_replies(&documents, article_date).len(), 0)
+        assert_eq!(parse_replies(&documents, article_date).0.len(), 0)
     }
 
     #[test]
@@ -681,6 +1360,516 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_edits() {
+        let documents = load_document("../tests/Gossiping_M.1123769450.A.A1A.html");
+        let article_date = parse_date(&documents).ok();
+        let edit = |hour: u32, min: u32| Edit {
+            editor_id: "MotherGoose".to_owned(),
+            ip: Some(Ipv4Addr::new(220, 132, 142, 235)),
+            date: Some(
+                FixedOffset::east(8 * 3600)
+                    .ymd(2005, 8, 11)
+                    .and_hms(hour, min, 0),
+            ),
+        };
+
+        assert_eq!(
+            parse_edits(&documents, article_date),
+            vec![edit(22, 31), edit(22, 31), edit(22, 32), edit(22, 33)]
+        );
+    }
+
+    #[test]
+    fn test_parse_edits_with_full_date() {
+        let documents = load_document("../tests/Soft_Job_M.1519661420.A.098.html");
+
+        assert_eq!(
+            parse_edits(&documents, parse_date(&documents).ok()),
+            vec![Edit {
+                editor_id: "adsl15888".to_owned(),
+                ip: Some(Ipv4Addr::new(113, 21, 55, 28)),
+                date: Some(
+                    FixedOffset::east(8 * 3600)
+                        .ymd(2018, 6, 10)
+                        .and_hms(9, 25, 49)
+                ),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_resolve_short_date_of_next_year() {
+        let article_date = FixedOffset::east(8 * 3600)
+            .ymd(2007, 12, 31)
+            .and_hms(23, 50, 0);
+
+        assert_eq!(
+            resolve_short_date("01/01 00:10", article_date),
+            Some(
+                FixedOffset::east(8 * 3600)
+                    .ymd(2008, 1, 1)
+                    .and_hms(0, 10, 0)
+            )
+        );
+        assert_eq!(
+            resolve_short_date("12/31 23:55", article_date),
+            Some(
+                FixedOffset::east(8 * 3600)
+                    .ymd(2007, 12, 31)
+                    .and_hms(23, 55, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_crossposts() {
+        let documents = load_document("../tests/Gossiping_M.1175469904.A.05B.html");
+        let crosspost = |board: &str| Crosspost {
+            user_id: "mitami".to_owned(),
+            board: board.parse().unwrap(),
+            date: Some(
+                FixedOffset::east(8 * 3600)
+                    .ymd(2007, 4, 2)
+                    .and_hms(12, 23, 0),
+            ),
+        };
+
+        assert_eq!(
+            parse_crossposts(&documents, parse_date(&documents).ok()),
+            vec![crosspost("BeautyBody"), crosspost("FITNESS")]
+        );
+        assert_eq!(parse_crosspost_source(&documents), None);
+    }
+
+    #[test]
+    fn test_parse_crosspost_source() {
+        let documents = Document::from(
+            r#"<div id="main-content" class="bbs-screen bbs-content"><div class="article-metaline"><span class="article-meta-tag">作者</span><span class="article-meta-value">someone (某人)</span></div><div class="article-metaline-right"><span class="article-meta-tag">看板</span><span class="article-meta-value">Test</span></div><div class="article-metaline"><span class="article-meta-tag">標題</span><span class="article-meta-value">[轉錄] 有沒有八卦</span></div><div class="article-metaline"><span class="article-meta-tag">時間</span><span class="article-meta-value">Sat Aug 15 12:00:00 2020</span></div>
+※ [本文轉錄自 Gossiping 看板 #1VDrjZHu ]
+
+作者: someone (某人) 看板: Gossiping
+有沒有八卦
+
+--
+<span class="f2">※ 發信站: 批踢踢實業坊(ptt.cc), 來自: 1.2.3.4 (臺灣)
+</span></div>"#,
+        );
+
+        assert_eq!(
+            parse_crosspost_source(&documents),
+            Some(CrosspostSource {
+                board: BoardName::Gossiping.into(),
+                aid: Some("1VDrjZHu".to_owned()),
+                id: Some("M.1597463395.A.478".to_owned()),
+            })
+        );
+        assert!(parse_content(&documents)
+            .unwrap()
+            .starts_with("※ [本文轉錄自 Gossiping 看板 #1VDrjZHu ]"));
+    }
+
+    #[test]
+    fn test_parse_content_body() {
+        let documents = load_document("../tests/Gossiping_M.1173456473.A.F4F.html");
+        let body = parse_content_body(&parse_quoted_content(&documents));
+
+        assert!(body.text.starts_with("喔喔~~終於有我可以回答的八卦了"));
+        assert!(body
+            .text
+            .ends_with("西班牙芒果牌全部三折時就是路邊攤的價錢了(我看到的只是都市)"));
+        assert_eq!(body.quotes, vec![]);
+        assert_eq!(body.signature, None);
+    }
+
+    #[test]
+    fn test_parse_content_body_with_quotes() {
+        let documents = load_document("../tests/Soft_Job_M.1181826032.A.5C2.html");
+        let content = parse_content(&documents).unwrap();
+        let body = parse_content_body(&parse_quoted_content(&documents));
+
+        assert!(content.starts_with("※ 引述《eric1125 (ERIC)》之銘言："));
+        assert!(content.ends_with("大家多多指教"));
+        assert_eq!(
+            body.text,
+            "我也來簽到\n\n大家多多指教\n\n\n原來大家都是新手\n我的筆記在這裡\nhttp://www.wretch.cc/blog/kenny0621"
+        );
+        assert_eq!(
+            body.quotes,
+            vec![
+                Quote {
+                    author_id: Some("eric1125".to_owned()),
+                    lines: vec![
+                        "恭喜恭喜阿".to_owned(),
+                        "身為資訊軟體人".to_owned(),
+                        "一定要來簽到一下".to_owned(),
+                        "以後大家多多交流吧".to_owned(),
+                        "我只是個小小菜鳥程式設計師...XD".to_owned(),
+                    ],
+                },
+                Quote {
+                    author_id: Some("johnson5566".to_owned()),
+                    lines: vec!["第一次在這邊發文".to_owned()],
+                },
+            ]
+        );
+        assert_eq!(
+            body.signature,
+            Some("寫程式的人不寫程式，就像鳥不會飛".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_parse_content_body_with_quotes_and_signature() {
+        let content = "※ 引述《someone (某人)》之銘言：\n: 有沒有八卦\n:\n: 的八卦\n\n我也想知道\n: 再問一次\n\n--\n簽名檔\n--";
+
+        assert_eq!(
+            parse_content_body(content),
+            ContentBody {
+                text: "我也想知道".to_owned(),
+                quotes: vec![
+                    Quote {
+                        author_id: Some("someone".to_owned()),
+                        lines: vec!["有沒有八卦".to_owned(), "".to_owned(), "的八卦".to_owned()],
+                    },
+                    Quote {
+                        author_id: None,
+                        lines: vec!["再問一次".to_owned()],
+                    },
+                ],
+                signature: Some("簽名檔".to_owned()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_links_of_content() {
+        let documents = load_document("../tests/Gossiping_M.1123769450.A.A1A.html");
+        let article = parse(&documents).unwrap();
+
+        assert_eq!(
+            article.links,
+            vec![
+                Link {
+                    url: "http://www.wretch.cc/album/mothergoose".to_owned(),
+                    kind: LinkKind::Other,
+                },
+                Link {
+                    url: "http://www.wretch.cc/user/mothergoose".to_owned(),
+                    kind: LinkKind::Other,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_links_after_quotes() {
+        let documents = load_document("../tests/Soft_Job_M.1181826032.A.5C2.html");
+
+        assert_eq!(
+            parse(&documents).unwrap().links,
+            vec![Link {
+                url: "http://www.wretch.cc/blog/kenny0621".to_owned(),
+                kind: LinkKind::Other,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_links_without_url_of_article() {
+        let documents = load_document("../tests/Soft_Job_M.1519661420.A.098.html");
+
+        assert_eq!(parse(&documents).unwrap().links, vec![]);
+    }
+
+    #[test]
+    fn test_reply_update_of_article_without_links() {
+        let documents = load_document("../tests/Gossiping_M.1119222611.A.7A9.html");
+        let refreshed = parse(&documents).unwrap();
+        let mut previous = refreshed.clone();
+        previous.replies.iter_mut().for_each(|r| r.links.clear());
+        assert!(refreshed.replies.iter().any(|r| !r.links.is_empty()));
+
+        let update = ReplyUpdate::diff(&previous, &refreshed);
+        assert_eq!(update.offset, previous.replies.len());
+        assert!(update.is_empty(&previous));
+    }
+
+    #[test]
+    fn test_parse_links_of_replies() {
+        let documents = load_document("../tests/Gossiping_M.1119222611.A.7A9.html");
+        let (replies, _) = parse_replies(&documents, None);
+        let links_of = |text: &str| {
+            replies
+                .iter()
+                .find(|r| r.content.contains(text))
+                .unwrap()
+                .links
+                .clone()
+        };
+
+        assert_eq!(
+            links_of("備份#1IqR2z8L"),
+            vec![Link {
+                url: "https://www.ptt.cc/bbs/Gossiping/M.1389473981.A.215.html".to_owned(),
+                kind: LinkKind::PttArticle {
+                    board: BoardName::Gossiping.into(),
+                    id: "M.1389473981.A.215".to_owned(),
+                    aid: Some("1IqR2z8L".to_owned()),
+                },
+            }]
+        );
+        assert_eq!(
+            links_of("ACTGGln3SG0"),
+            vec![Link {
+                url: "https://www.youtube.com/watch?v=ACTGGln3SG0".to_owned(),
+                kind: LinkKind::YouTube,
+            }]
+        );
+        assert_eq!(
+            links_of("ugtuyRO"),
+            vec![Link {
+                url: "https://i.imgur.com/ugtuyRO.jpg".to_owned(),
+                kind: LinkKind::Image,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_links_in_text() {
+        let links = parse_links(
+            std::iter::empty(),
+            "圖 https://imgur.com/a/3k37x 影片http://youtu.be/DVEx063S4rU，原文 #1VDrjZHu (Gossiping)",
+        );
+
+        assert_eq!(
+            links,
+            vec![
+                Link {
+                    url: "https://imgur.com/a/3k37x".to_owned(),
+                    kind: LinkKind::Image,
+                },
+                Link {
+                    url: "http://youtu.be/DVEx063S4rU".to_owned(),
+                    kind: LinkKind::YouTube,
+                },
+                Link {
+                    url: "https://www.ptt.cc/bbs/Gossiping/M.1597463395.A.478.html".to_owned(),
+                    kind: LinkKind::PttArticle {
+                        board: BoardName::Gossiping.into(),
+                        id: "M.1597463395.A.478".to_owned(),
+                        aid: Some("1VDrjZHu".to_owned()),
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify_link() {
+        assert_eq!(
+            classify_link("https://i.imgur.com/tnlLDAU.jpg"),
+            LinkKind::Image
+        );
+        assert_eq!(
+            classify_link("https://example.com/cat.PNG"),
+            LinkKind::Image
+        );
+        assert_eq!(
+            classify_link("https://m.youtube.com/watch?v=t-cNdH2ZBII"),
+            LinkKind::YouTube
+        );
+        assert_eq!(
+            classify_link("https://www.ptt.cc/bbs/Gossiping/index.html"),
+            LinkKind::Other
+        );
+        assert_eq!(classify_link("https://reurl.cc/3q05M"), LinkKind::Other);
+    }
+
+    #[test]
+    fn test_parse_listing() {
+        let document = Document::from(LISTING_HTML);
+        let entries = parse_listing(&document, "https://www.ptt.cc", Some(39000));
+
+        assert_eq!(entries.len(), 4);
+        assert_eq!(
+            entries[0],
+            BoardListingEntry {
+                push_count: PushCount::Explosive,
+                mark: Some("M".to_owned()),
+                date: "8/15".to_owned(),
+                author_id: "someone".to_owned(),
+                title: "[問卦] 有沒有八卦".to_owned(),
+                url: Some("https://www.ptt.cc/bbs/Gossiping/M.1597463395.A.478.html".to_owned()),
+                pinned: false,
+                page: Some(39000),
+                position: 0,
+                deletion: None,
+            }
+        );
+        assert_eq!(entries[1].push_count, PushCount::NegativeTens(2));
+        assert_eq!(entries[1].title, "(本文已被刪除) [another]");
+        assert_eq!(entries[1].url, None);
+        assert_eq!(entries[1].mark, None);
+        assert_eq!(entries[1].position, 1);
+        assert_eq!(entries[3].push_count, PushCount::Count(0));
+        assert!(entries[3].pinned);
+    }
+
+    #[test]
+    fn test_parse_listing_deleted_entries() {
+        let document = Document::from(LISTING_HTML);
+        let entries = parse_listing(&document, "https://www.ptt.cc", None);
+
+        assert_eq!(
+            entries[1].deletion,
+            Some(Deletion {
+                deleted_by: Some("another".to_owned()),
+                author_id: Some("another".to_owned()),
+            })
+        );
+        assert_eq!(
+            entries[2].deletion,
+            Some(Deletion {
+                deleted_by: Some("moderator".to_owned()),
+                author_id: Some("writer".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_board_entries() {
+        let document = Document::from(
+            r#"
+<div class="b-list-container action-bar-margin bbs-screen">
+  <div class="b-ent">
+    <a class="board" href="/bbs/Gossiping/index.html">
+      <div class="board-name">Gossiping</div>
+      <div class="board-nuser"><span class="hl f6">13580</span></div>
+      <div class="board-class">綜合</div>
+      <div class="board-title">◎[八卦]不是我愛八卦 只是愛分享</div>
+    </a>
+  </div>
+  <div class="b-ent">
+    <a class="board" href="/cls/2">
+      <div class="board-name">1PTTCS</div>
+      <div class="board-nuser"></div>
+      <div class="board-class"></div>
+      <div class="board-title">資訊專業、科技 Σ</div>
+    </a>
+  </div>
+</div>
+"#,
+        );
+        let entries = parse_board_entries(&document, "https://www.ptt.cc");
+
+        assert_eq!(
+            entries,
+            vec![
+                BoardEntry::Board(BoardInfo {
+                    name: BoardName::Gossiping.into(),
+                    title: "◎[八卦]不是我愛八卦 只是愛分享".to_owned(),
+                    category: "綜合".to_owned(),
+                    user_count: Some(13580),
+                    url: "https://www.ptt.cc/bbs/Gossiping/index.html".to_owned(),
+                }),
+                BoardEntry::Category(CategoryInfo {
+                    name: "1PTTCS".to_owned(),
+                    title: "資訊專業、科技 Σ".to_owned(),
+                    url: "https://www.ptt.cc/cls/2".to_owned(),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_man_entries() {
+        let document = Document::from(
+            r#"<div class="m-ent">
+                <div class="title">◆ <a href="/man/Gossiping/D8D5/index.html">[精華] 八卦</a></div>
+                <div class="date"></div>
+            </div>
+            <div class="m-ent">
+                <div class="title">◇ <a href="/man/Gossiping/D8D5/M.1123769450.A.A1A.html">[問卦] 有沒有八卦</a></div>
+                <div class="date">2005/08/11</div>
+            </div>
+            <div class="m-ent">
+                <div class="title">◇ (本文已被刪除)</div>
+            </div>"#,
+        );
+
+        assert_eq!(
+            parse_man_entries(&document, "https://www.ptt.cc"),
+            vec![
+                ManListingEntry {
+                    title: "[精華] 八卦".to_owned(),
+                    url: "https://www.ptt.cc/man/Gossiping/D8D5/index.html".to_owned(),
+                    is_directory: true,
+                },
+                ManListingEntry {
+                    title: "[問卦] 有沒有八卦".to_owned(),
+                    url: "https://www.ptt.cc/man/Gossiping/D8D5/M.1123769450.A.A1A.html".to_owned(),
+                    is_directory: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_feed() {
+        let document = Document::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <feed xmlns="http://www.w3.org/2005/Atom">
+              <title>看板 Gossiping 文章列表</title>
+              <id>https://www.ptt.cc/bbs/Gossiping/index.html</id>
+              <updated>2020-08-15T03:51:00Z</updated>
+              <link href="https://www.ptt.cc/bbs/Gossiping/index.html"></link>
+              <entry>
+                <title>[問卦] 有沒有八卦</title>
+                <link href="https://www.ptt.cc/bbs/Gossiping/M.1597463395.A.478.html"></link>
+                <id>https://www.ptt.cc/bbs/Gossiping/M.1597463395.A.478.html</id>
+                <author>
+                  <name>someone</name>
+                </author>
+                <published>2020-08-15T03:49:55Z</published>
+                <updated>2020-08-15T03:50:10Z</updated>
+                <content type="html">&lt;pre&gt;有沒有八卦&lt;/pre&gt;</content>
+              </entry>
+              <entry>
+                <title>Re: [問卦] 有沒有八卦</title>
+                <id>https://www.ptt.cc/bbs/Gossiping/M.1597463400.A.001.html</id>
+                <author>
+                  <name>another</name>
+                </author>
+                <published>2020-08-15T11:50:00+08:00</published>
+              </entry>
+            </feed>"#,
+        );
+
+        let utc = FixedOffset::east(0);
+        assert_eq!(
+            parse_feed(&document),
+            vec![
+                FeedEntry {
+                    id: "M.1597463395.A.478".to_owned(),
+                    title: "[問卦] 有沒有八卦".to_owned(),
+                    author_id: "someone".to_owned(),
+                    published: Some(utc.ymd(2020, 8, 15).and_hms(3, 49, 55)),
+                    updated: Some(utc.ymd(2020, 8, 15).and_hms(3, 50, 10)),
+                    url: "https://www.ptt.cc/bbs/Gossiping/M.1597463395.A.478.html".to_owned(),
+                },
+                FeedEntry {
+                    id: "M.1597463400.A.001".to_owned(),
+                    title: "Re: [問卦] 有沒有八卦".to_owned(),
+                    author_id: "another".to_owned(),
+                    published: Some(TW_TIME_OFFSET.ymd(2020, 8, 15).and_hms(11, 50, 0)),
+                    updated: None,
+                    url: "https://www.ptt.cc/bbs/Gossiping/M.1597463400.A.001.html".to_owned(),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_malformed_content() {
         let documents = load_document("../tests/Gossiping_M.1519661420.A.098.html");
@@ -0,0 +1,183 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+/// Clock provides the current time to a RateLimiter.
+/// Replace it to control the time in tests.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// SystemClock is a Clock reading the time from the operating system.
+#[derive(Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// RateLimiter is a token bucket limiting how often requests are sent.
+///
+/// The bucket holds at most `burst` tokens and is refilled with
+/// `requests_per_second` tokens per second. Every request takes one token and
+/// waits until the token is available.
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: u32,
+    jitter: Option<Duration>,
+    clock: Arc<dyn Clock>,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl RateLimiter {
+    /// Return a RateLimiter allowing `requests_per_second` requests per second
+    /// on average and at most `burst` requests at once.
+    pub fn new(requests_per_second: f64, burst: u32) -> RateLimiter {
+        RateLimiter::with_clock(requests_per_second, burst, Arc::new(SystemClock))
+    }
+
+    /// Same as `new` but reads the time from given clock.
+    pub fn with_clock(requests_per_second: f64, burst: u32, clock: Arc<dyn Clock>) -> RateLimiter {
+        let burst = burst.max(1);
+        let updated_at = clock.now();
+        RateLimiter {
+            requests_per_second,
+            burst,
+            jitter: None,
+            clock,
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(burst),
+                updated_at,
+            }),
+        }
+    }
+
+    /// Add a random delay between zero and `jitter` to every request.
+    pub fn jitter(mut self, jitter: Duration) -> RateLimiter {
+        self.jitter = Some(jitter);
+        self
+    }
+
+    /// Take a token from the bucket and return how long the caller has to
+    /// wait before sending its request. Jitter is not included.
+    pub fn reserve(&self) -> Duration {
+        let now = self.clock.now();
+        let mut bucket = self.bucket.lock().unwrap();
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.requests_per_second)
+            .min(f64::from(self.burst));
+        bucket.updated_at = now;
+        // tokens may go negative, so that callers queue up behind each other
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 || self.requests_per_second <= 0.0 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.requests_per_second)
+        }
+    }
+
+    /// Wait until a request is allowed to be sent.
+    pub async fn acquire(&self) {
+        let mut delay = self.reserve();
+        if let Some(jitter) = self.jitter {
+            delay += random_duration(jitter);
+        }
+        if delay > Duration::from_secs(0) {
+            debug!("Rate limited, wait for {:?}", delay);
+            tokio::time::delay_for(delay).await;
+        }
+    }
+}
+
+fn random_duration(max: Duration) -> Duration {
+    if max == Duration::from_secs(0) {
+        return max;
+    }
+    let nanos = rand::thread_rng().gen_range(0, max.as_nanos() as u64);
+    Duration::from_nanos(nanos)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    struct MockClock {
+        now: Mutex<Instant>,
+    }
+
+    impl MockClock {
+        fn new() -> MockClock {
+            MockClock {
+                now: Mutex::new(Instant::now()),
+            }
+        }
+
+        fn advance(&self, duration: Duration) {
+            *self.now.lock().unwrap() += duration;
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            *self.now.lock().unwrap()
+        }
+    }
+
+    #[test]
+    fn test_reserve_within_burst() {
+        let clock = Arc::new(MockClock::new());
+        let limiter = RateLimiter::with_clock(1.0, 3, clock);
+
+        for _ in 0..3 {
+            assert_eq!(limiter.reserve(), Duration::from_secs(0));
+        }
+    }
+
+    #[test]
+    fn test_reserve_over_burst() {
+        let clock = Arc::new(MockClock::new());
+        let limiter = RateLimiter::with_clock(2.0, 1, clock);
+
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+        assert_eq!(limiter.reserve(), Duration::from_millis(500));
+        assert_eq!(limiter.reserve(), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_reserve_after_refill() {
+        let clock = Arc::new(MockClock::new());
+        let limiter = RateLimiter::with_clock(1.0, 2, clock.clone());
+
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+        assert_eq!(limiter.reserve(), Duration::from_secs(1));
+
+        // refilled tokens never exceed the burst size
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+        assert_eq!(limiter.reserve(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_random_duration_within_max() {
+        let max = Duration::from_millis(10);
+        for _ in 0..100 {
+            assert!(random_duration(max) < max);
+        }
+        assert_eq!(
+            random_duration(Duration::from_secs(0)),
+            Duration::from_secs(0)
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use std::time::{Duration, SystemTime};
+
+use chrono::DateTime;
+use reqwest::StatusCode;
+
+/// RetryPolicy decides whether and when a failed request should be sent again.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled after every failed retry
+    pub base_delay: Duration,
+    /// Maximum delay between two attempts
+    pub max_delay: Duration,
+    /// Status codes of responses which should be retried
+    pub retryable_statuses: Vec<StatusCode>,
+    /// Whether requests failed with timeout should be retried
+    pub retry_timeout: bool,
+    /// Whether requests failed to connect should be retried
+    pub retry_connect: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            retryable_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_timeout: true,
+            retry_connect: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Return a RetryPolicy which never retries.
+    pub fn never() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Return the delay before sending the request again after given failed attempt.
+    /// The first attempt is numbered 1.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self.base_delay.checked_mul(1 << exponent);
+        match delay {
+            Some(d) if d < self.max_delay => d,
+            _ => self.max_delay,
+        }
+    }
+
+    /// Return the delay before retrying after given failed attempt, which is
+    /// the delay requested by Retry-After header if any, but never longer than
+    /// `max_delay`.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        match retry_after {
+            Some(d) => d.min(self.max_delay),
+            None => self.backoff(attempt),
+        }
+    }
+
+    pub fn is_retryable_status(&self, status: StatusCode) -> bool {
+        self.retryable_statuses.contains(&status)
+    }
+
+    pub fn is_retryable_error(&self, error: &reqwest::Error) -> bool {
+        (self.retry_timeout && error.is_timeout()) || (self.retry_connect && error.is_connect())
+    }
+}
+
+/// Parse the value of a Retry-After header, which is either a number of
+/// seconds or a HTTP date, into the delay counted from `now`.
+pub fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let date = SystemTime::UNIX_EPOCH + Duration::from_secs(date.timestamp().max(0) as u64);
+    Some(
+        date.duration_since(now)
+            .unwrap_or_else(|_| Duration::from_secs(0)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(100), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_delay_with_retry_after() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(30),
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.delay(1, None), Duration::from_millis(100));
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(5))),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(86400))),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        let policy = RetryPolicy::default();
+
+        assert!(policy.is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!policy.is_retryable_status(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        assert_eq!(
+            parse_retry_after(" 120 ", SystemTime::now()),
+            Some(Duration::from_secs(120))
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after_date() {
+        // Wed, 21 Oct 2015 07:28:00 GMT
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_445_412_420);
+
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            parse_retry_after(
+                "Wed, 21 Oct 2015 07:28:00 GMT",
+                now + Duration::from_secs(120)
+            ),
+            Some(Duration::from_secs(0))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_retry_after() {
+        assert_eq!(parse_retry_after("soon", SystemTime::now()), None);
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt;
+
+/// SearchQuery builds the query of searching articles in a board, which
+/// supports keywords and the operators of PTT web search.
+///
+/// ```
+/// use ptt_crawler::search::SearchQuery;
+///
+/// let query = SearchQuery::new().keyword("疫苗").author("someone").recommend(10);
+/// assert_eq!(query.to_string(), "疫苗 author:someone recommend:10");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchQuery {
+    keywords: Vec<String>,
+    author: Option<String>,
+    recommend: Option<i32>,
+    thread: Option<String>,
+}
+
+impl SearchQuery {
+    pub fn new() -> SearchQuery {
+        SearchQuery::default()
+    }
+
+    /// Search articles whose title contains given keyword.
+    pub fn keyword<S: Into<String>>(mut self, keyword: S) -> SearchQuery {
+        self.keywords.push(keyword.into());
+        self
+    }
+
+    /// Search articles posted by given author ID.
+    pub fn author<S: Into<String>>(mut self, author_id: S) -> SearchQuery {
+        self.author = Some(author_id.into());
+        self
+    }
+
+    /// Search articles with at least given push count.
+    /// Negative count searches articles with at most given push count.
+    pub fn recommend(mut self, count: i32) -> SearchQuery {
+        self.recommend = Some(count);
+        self
+    }
+
+    /// Search articles in the same thread of given title.
+    pub fn thread<S: Into<String>>(mut self, title: S) -> SearchQuery {
+        self.thread = Some(title.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keywords.is_empty()
+            && self.author.is_none()
+            && self.recommend.is_none()
+            && self.thread.is_none()
+    }
+}
+
+impl fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut terms: Vec<String> = self.keywords.clone();
+        if let Some(author) = &self.author {
+            terms.push(format!("author:{}", author));
+        }
+        if let Some(recommend) = self.recommend {
+            terms.push(format!("recommend:{}", recommend));
+        }
+        // title of thread may contain spaces, so it goes last
+        if let Some(thread) = &self.thread {
+            terms.push(format!("thread:{}", thread));
+        }
+        f.write_str(&terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_keywords() {
+        let query = SearchQuery::new().keyword("八卦").keyword("問卦");
+
+        assert_eq!(query.to_string(), "八卦 問卦");
+    }
+
+    #[test]
+    fn test_operators() {
+        let query = SearchQuery::new()
+            .thread("有沒有八卦 的八卦")
+            .recommend(-10)
+            .author("someone");
+
+        assert_eq!(
+            query.to_string(),
+            "author:someone recommend:-10 thread:有沒有八卦 的八卦"
+        );
+    }
+
+    #[test]
+    fn test_empty_query() {
+        assert!(SearchQuery::new().is_empty());
+        assert!(!SearchQuery::new().author("someone").is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::article::{Article, Board};
+
+lazy_static! {
+    static ref REPLY_PREFIX: Regex = Regex::new(r"(?i)^\s*(re|fw|fwd)\s*[:：]").unwrap();
+    static ref TITLE_PREFIX: Regex =
+        Regex::new(r"(?i)^\s*((re|fw|fwd)\s*[:：]\s*|[\[［][^\]］]*[\]］]\s*)+").unwrap();
+}
+
+/// Thread represents an original article and articles replying or forwarding it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Thread {
+    pub board: Board,
+    /// Title without "Re:", "Fw:" and category, e.g. "有沒有八卦" of "Re: [問卦] 有沒有八卦"
+    pub title: String,
+    /// ID of the oldest article which is neither a reply nor a forward, if any
+    pub root_id: Option<String>,
+    /// Articles sorted from the oldest one
+    pub articles: Vec<Article>,
+}
+
+impl Thread {
+    /// Create a Thread of given articles, which are sorted by date.
+    pub fn new(board: Board, title: String, mut articles: Vec<Article>) -> Thread {
+        articles.sort_by_key(|a| a.meta.timestamp());
+        let root_id = articles
+            .iter()
+            .find(|a| !is_reply_title(&a.meta.title))
+            .map(|a| a.meta.id.clone());
+        Thread {
+            board,
+            title,
+            root_id,
+            articles,
+        }
+    }
+}
+
+/// Return the title without leading "Re:", "Fw:" and categories.
+/// e.g. "有沒有八卦" of "Re: Fw: [問卦] 有沒有八卦"
+pub fn normalize_title(title: &str) -> String {
+    TITLE_PREFIX.replace(title, "").trim().to_owned()
+}
+
+/// Whether the title is of an article replying or forwarding another one.
+pub fn is_reply_title(title: &str) -> bool {
+    REPLY_PREFIX.is_match(title)
+}
+
+/// Group articles with the same board and normalized title into Threads.
+/// Articles in each Thread are sorted by date, and the Threads are sorted by
+/// date of their oldest articles.
+pub fn group_threads(articles: Vec<Article>) -> Vec<Thread> {
+    let mut groups: Vec<(Board, String, Vec<Article>)> = vec![];
+    let mut indices: HashMap<(Board, String), usize> = HashMap::new();
+    for article in articles {
+        let title = normalize_title(&article.meta.title);
+        let key = (article.meta.board.clone(), title);
+        let index = *indices.entry(key.clone()).or_insert_with(|| {
+            groups.push((key.0, key.1, vec![]));
+            groups.len() - 1
+        });
+        groups[index].2.push(article);
+    }
+
+    let mut threads: Vec<Thread> = groups
+        .into_iter()
+        .map(|(board, title, articles)| Thread::new(board, title, articles))
+        .collect();
+    threads.sort_by_key(|t| t.articles.first().and_then(|a| a.meta.timestamp()));
+    threads
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::article::{test_article, BoardName};
+
+    use super::*;
+
+    fn article(board: BoardName, id: &str, title: &str) -> Article {
+        let mut article = test_article(id);
+        article.meta.board = board.into();
+        article.meta.title = title.to_owned();
+        article
+    }
+
+    #[test]
+    fn test_normalize_title() {
+        assert_eq!(normalize_title("有沒有八卦"), "有沒有八卦");
+        assert_eq!(normalize_title("Re: [問卦] 有沒有八卦"), "有沒有八卦");
+        assert_eq!(normalize_title("RE:Fw: ［新聞］ 有沒有八卦 "), "有沒有八卦");
+    }
+
+    #[test]
+    fn test_is_reply_title() {
+        assert!(is_reply_title("Re: [問卦] 有沒有八卦"));
+        assert!(is_reply_title("Fw: 有沒有八卦"));
+        assert!(!is_reply_title("[問卦] 有沒有Re:的八卦"));
+    }
+
+    #[test]
+    fn test_group_threads() {
+        let threads = group_threads(vec![
+            article(
+                BoardName::Gossiping,
+                "M.1597000300.A.003",
+                "Re: [問卦] 八卦",
+            ),
+            article(BoardName::Gossiping, "M.1597000200.A.002", "其他"),
+            article(BoardName::Gossiping, "M.1597000100.A.001", "八卦"),
+            article(BoardName::Stock, "M.1597000400.A.004", "Re: 八卦"),
+        ]);
+
+        let summary: Vec<(String, Option<String>, Vec<String>)> = threads
+            .into_iter()
+            .map(|t| {
+                (
+                    t.title,
+                    t.root_id,
+                    t.articles.into_iter().map(|a| a.meta.id).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "八卦".to_owned(),
+                    Some("M.1597000100.A.001".to_owned()),
+                    vec![
+                        "M.1597000100.A.001".to_owned(),
+                        "M.1597000300.A.003".to_owned()
+                    ]
+                ),
+                (
+                    "其他".to_owned(),
+                    Some("M.1597000200.A.002".to_owned()),
+                    vec!["M.1597000200.A.002".to_owned()]
+                ),
+                (
+                    "八卦".to_owned(),
+                    None,
+                    vec!["M.1597000400.A.004".to_owned()]
+                ),
+            ]
+        );
+    }
+}
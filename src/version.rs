@@ -0,0 +1,324 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use chrono::{DateTime, FixedOffset};
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+use crate::article::{Article, Board};
+use crate::json_file::{load_json, save_json};
+
+/// FieldChange represents a field of article changed between two versions.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub old: String,
+    pub new: String,
+}
+
+/// LineChange represents a line of content added or removed between two versions.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum LineChange {
+    /// Line added at given line number of new content, starting from 0
+    Added(usize, String),
+    /// Line removed from given line number of old content, starting from 0
+    Removed(usize, String),
+}
+
+/// ArticleDiff represents the changes of title, category and content between
+/// two versions of an article.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ArticleDiff {
+    pub title: Option<FieldChange>,
+    pub category: Option<FieldChange>,
+    pub content: Vec<LineChange>,
+}
+
+impl ArticleDiff {
+    /// Compare the title, category and content of old and new versions of an article.
+    pub fn new(old: &Article, new: &Article) -> ArticleDiff {
+        let field_change = |old: &str, new: &str| {
+            if old == new {
+                None
+            } else {
+                Some(FieldChange {
+                    old: old.to_owned(),
+                    new: new.to_owned(),
+                })
+            }
+        };
+        ArticleDiff {
+            title: field_change(&old.meta.title, &new.meta.title),
+            category: field_change(&old.meta.category, &new.meta.category),
+            content: diff_lines(&old.content, &new.content),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.category.is_none() && self.content.is_empty()
+    }
+}
+
+/// ArticleVersion represents an article crawled at a time, with the changes
+/// from its previous version.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ArticleVersion {
+    pub crawled_at: DateTime<FixedOffset>,
+    pub article: Article,
+    /// Changes from previous version, or None for the first version
+    pub diff: Option<ArticleDiff>,
+}
+
+/// VersionHistory stores the versions of an article, from the oldest one.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VersionHistory {
+    pub board: Board,
+    pub id: String,
+    pub versions: Vec<ArticleVersion>,
+}
+
+impl VersionHistory {
+    pub fn new(article: Article, crawled_at: DateTime<FixedOffset>) -> VersionHistory {
+        VersionHistory {
+            board: article.meta.board.clone(),
+            id: article.meta.id.clone(),
+            versions: vec![ArticleVersion {
+                crawled_at,
+                article,
+                diff: None,
+            }],
+        }
+    }
+
+    pub fn latest(&self) -> &Article {
+        &self.versions.last().unwrap().article
+    }
+
+    /// Record the article as a new version if it was changed from the latest
+    /// version, and return the changes.
+    pub fn record(
+        &mut self,
+        article: Article,
+        crawled_at: DateTime<FixedOffset>,
+    ) -> Option<ArticleDiff> {
+        let diff = ArticleDiff::new(self.latest(), &article);
+        if diff.is_empty() {
+            return None;
+        }
+        self.versions.push(ArticleVersion {
+            crawled_at,
+            article,
+            diff: Some(diff.clone()),
+        });
+        Some(diff)
+    }
+}
+
+/// VersionChange represents the changes of an article found at a crawl.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct VersionChange {
+    pub board: Board,
+    pub id: String,
+    pub crawled_at: DateTime<FixedOffset>,
+    pub diff: ArticleDiff,
+}
+
+/// VersionStore stores the VersionHistory of every tracked article by its board and ID,
+/// since the same ID may appear on different boards, e.g. crossposts.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct VersionStore {
+    pub histories: BTreeMap<Board, BTreeMap<String, VersionHistory>>,
+}
+
+impl VersionStore {
+    pub fn new() -> VersionStore {
+        VersionStore::default()
+    }
+
+    /// Load a VersionStore from the JSON file at given path.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<VersionStore> {
+        load_json(path)
+    }
+
+    /// Save the VersionStore as JSON file at given path.
+    /// The file is replaced only after the whole VersionStore is written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        save_json(self, path)
+    }
+
+    /// Get the VersionHistory of the article on given board.
+    pub fn get(&self, board: &Board, id: &str) -> Option<&VersionHistory> {
+        self.histories.get(board).and_then(|h| h.get(id))
+    }
+
+    /// Iterate over the VersionHistory of every tracked article.
+    pub fn iter(&self) -> impl Iterator<Item = &VersionHistory> {
+        self.histories.values().flat_map(|h| h.values())
+    }
+
+    /// Record the article as a new version of its history, and return the
+    /// changes from the latest version. The first version of an article has
+    /// no changes.
+    pub fn record(
+        &mut self,
+        article: Article,
+        crawled_at: DateTime<FixedOffset>,
+    ) -> Option<VersionChange> {
+        let histories = self
+            .histories
+            .entry(article.meta.board.clone())
+            .or_default();
+        match histories.get_mut(&article.meta.id) {
+            Some(history) => history
+                .record(article, crawled_at)
+                .map(|diff| VersionChange {
+                    board: history.board.clone(),
+                    id: history.id.clone(),
+                    crawled_at,
+                    diff,
+                }),
+            None => {
+                let history = VersionHistory::new(article, crawled_at);
+                histories.insert(history.id.clone(), history);
+                None
+            }
+        }
+    }
+}
+
+/// Compare old and new text line by line with Myers' diff algorithm.
+fn diff_lines(old: &str, new: &str) -> Vec<LineChange> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let removed = |index: usize, len: usize| {
+        (index..index + len).map(|i| LineChange::Removed(i, old[i].to_owned()))
+    };
+    let added = |index: usize, len: usize| {
+        (index..index + len).map(|i| LineChange::Added(i, new[i].to_owned()))
+    };
+
+    let mut changes = vec![];
+    for op in capture_diff_slices(Algorithm::Myers, &old, &new) {
+        match op {
+            DiffOp::Equal { .. } => {}
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => changes.extend(removed(old_index, old_len)),
+            DiffOp::Insert {
+                new_index, new_len, ..
+            } => changes.extend(added(new_index, new_len)),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => {
+                changes.extend(removed(old_index, old_len));
+                changes.extend(added(new_index, new_len));
+            }
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    use crate::article::{test_article, BoardName};
+
+    use super::*;
+
+    fn article(title: &str, content: &str) -> Article {
+        let mut article = test_article("M.1597463395.A.478");
+        article.meta.category = "問卦".to_owned();
+        article.meta.title = title.to_owned();
+        article.content = content.to_owned();
+        article
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(
+            diff_lines("a\nb\nc\nd", "a\nc\nd\ne"),
+            vec![
+                LineChange::Removed(1, "b".to_owned()),
+                LineChange::Added(3, "e".to_owned()),
+            ]
+        );
+        assert_eq!(diff_lines("a\nb", "a\nb"), vec![]);
+    }
+
+    #[test]
+    fn test_article_diff() {
+        let diff = ArticleDiff::new(&article("八卦", "a\nb"), &article("有沒有八卦", "a\nc"));
+
+        assert_eq!(
+            diff,
+            ArticleDiff {
+                title: Some(FieldChange {
+                    old: "八卦".to_owned(),
+                    new: "有沒有八卦".to_owned(),
+                }),
+                category: None,
+                content: vec![
+                    LineChange::Removed(1, "b".to_owned()),
+                    LineChange::Added(1, "c".to_owned()),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_record_versions() {
+        let time = FixedOffset::east(8 * 3600)
+            .ymd(2020, 8, 15)
+            .and_hms(12, 0, 0);
+        let mut store = VersionStore::new();
+
+        assert_eq!(store.record(article("八卦", "a"), time), None);
+        assert_eq!(store.record(article("八卦", "a"), time), None);
+        let change = store.record(article("八卦", "a\nb"), time).unwrap();
+        assert_eq!(change.id, "M.1597463395.A.478");
+        assert_eq!(
+            change.diff.content,
+            vec![LineChange::Added(1, "b".to_owned())]
+        );
+
+        let board = Board::from(BoardName::Gossiping);
+        let history = store.get(&board, "M.1597463395.A.478").unwrap();
+        assert_eq!(history.versions.len(), 2);
+        assert_eq!(history.latest().content, "a\nb");
+    }
+
+    #[test]
+    fn test_record_same_id_on_different_boards() {
+        let time = FixedOffset::east(8 * 3600)
+            .ymd(2020, 8, 15)
+            .and_hms(12, 0, 0);
+        let mut store = VersionStore::new();
+        let mut crosspost = article("八卦", "b");
+        crosspost.meta.board = BoardName::Joke.into();
+
+        assert_eq!(store.record(article("八卦", "a"), time), None);
+        assert_eq!(store.record(crosspost, time), None);
+
+        assert_eq!(store.iter().count(), 2);
+        let board = Board::from(BoardName::Gossiping);
+        let history = store.get(&board, "M.1597463395.A.478").unwrap();
+        assert_eq!(history.latest().content, "a");
+    }
+
+    #[test]
+    fn test_version_store_serde() {
+        let time = FixedOffset::east(8 * 3600)
+            .ymd(2020, 8, 15)
+            .and_hms(12, 0, 0);
+        let mut store = VersionStore::new();
+        store.record(article("八卦", "a"), time);
+
+        let json = serde_json::to_string(&store).unwrap();
+        let store: VersionStore = serde_json::from_str(&json).unwrap();
+        assert_eq!(store.iter().count(), 1);
+    }
+}